@@ -11,7 +11,6 @@ use derivative::Derivative;
 use crate::events::EventTrait;
 use crate as engine;
 use component_derive::Event;
-use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
 
 #[derive(Event, Debug, PartialEq, Clone)]
 pub struct PlayTune(pub usize, pub Vec<(Duration, Note)>);
@@ -100,35 +99,6 @@ impl EnvSpec {
     }
 }
 
-pub fn initialise_audio(sdl_context: &sdl2::Sdl) -> Result<AudioDevice<AudioPlayer>, String> {
-    let audio_subsystem = sdl_context.audio()?;
-
-    let desired_spec = AudioSpecDesired {
-        freq: Some(48000),
-        channels: Some(1),  // mono
-        samples: Some(1024) 
-    };
-
-    let audio_device = audio_subsystem.open_playback(None, &desired_spec, |spec| {
-        // initialize the audio callback
-        AudioPlayer {
-            rng: SmallRng::from_entropy(),
-            freq: spec.freq,
-            cycles: 0,
-            queue: BinaryHeap::new(),
-            channel: [Channel::Silence {}, Channel::Silence{}, Channel::Silence {}, Channel::Silence{}]
-        }
-    }).unwrap();
-
-    // Start playback
-    audio_device.resume();
-    Ok(audio_device)
-}
-
-pub fn play_tune(device: &mut AudioDevice<AudioPlayer>, PlayTune(channel, tune): &PlayTune) {
-    device.lock().cue(*channel, tune);
-}
-
 struct Wave {
     phase_inc: f32,
     phase: f32,
@@ -194,7 +164,9 @@ pub fn partial_reversed<T: Ord>(first: &T, second: &T) -> Option<Ordering> {
 }
 
 
-pub struct AudioPlayer {
+// the synth on its own, knowing nothing of where its samples go: it's handed a buffer to fill, one sample
+// at a time at the rate it was made for, and plays whatever notes have come due along the way
+pub struct Mixer {
     rng: SmallRng,
     freq: i32,
     cycles: u64,
@@ -202,16 +174,26 @@ pub struct AudioPlayer {
     channel: [Channel;4]
 }
 
-impl AudioPlayer {
+impl Mixer {
+    pub fn new(freq: i32) -> Self {
+        Mixer {
+            rng: SmallRng::from_entropy(),
+            freq,
+            cycles: 0,
+            queue: BinaryHeap::new(),
+            channel: [Channel::Silence {}, Channel::Silence{}, Channel::Silence {}, Channel::Silence{}]
+        }
+    }
+
+    pub fn freq(&self) -> i32 {
+        self.freq
+    }
+
     fn set_channel(&mut self, channel: Channel, channel_no: usize) {
         self.channel[channel_no] = channel;
     }
-}
 
-impl AudioCallback for AudioPlayer {
-    type Channel = f32;
-
-    fn callback(&mut self, out: &mut [f32]) {
+    pub fn mix(&mut self, out: &mut [f32]) {
         for x in out.iter_mut() {
 
             while let Some((channel, note)) = self.due() {
@@ -235,15 +217,12 @@ impl AudioCallback for AudioPlayer {
             self.cycles += 1;
         }
     } 
-}
-
-impl AudioPlayer {
 
-    fn cue(&mut self, channel: usize, tune: &Vec<(Duration, Note)>) {
+    pub fn cue(&mut self, PlayTune(channel, tune): &PlayTune) {
         for (delay, note) in tune {
             let cycles_before_start = (delay.as_secs_f64() * self.freq as f64) as u64;
             let start_at = cycles_before_start + self.cycles;
-            self.queue.push(Cue { start_at, channel, note: note.clone() });
+            self.queue.push(Cue { start_at, channel: *channel, note: note.clone() });
         }
     }
 
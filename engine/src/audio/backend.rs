@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use super::audio::{Mixer, PlayTune};
+
+// wherever tunes end up being heard, or not
+pub trait AudioBackend {
+    fn play_tune(&mut self, tune: &PlayTune);
+
+    // drops everything that's been cued and not yet started
+    fn clear(&mut self);
+}
+
+// for when there's nothing to play through: tunes are accepted and forgotten
+pub struct NullAudio;
+
+impl AudioBackend for NullAudio {
+    fn play_tune(&mut self, _tune: &PlayTune) {}
+
+    fn clear(&mut self) {}
+}
+
+// mixes into memory instead of a device, only as far as it's asked to, so what would have been heard
+// can be checked or saved without any audio hardware
+pub struct OfflineAudio {
+    mixer: Mixer,
+    samples: Vec<f32>,
+}
+
+impl OfflineAudio {
+    pub fn new(freq: i32) -> Self {
+        OfflineAudio { mixer: Mixer::new(freq), samples: Vec::new() }
+    }
+
+    pub fn render(&mut self, duration: &Duration) -> &[f32] {
+        let start = self.samples.len();
+        let count = (duration.as_secs_f64() * self.mixer.freq() as f64).round() as usize;
+        self.samples.resize(start + count, 0.0);
+        self.mixer.mix(&mut self.samples[start..]);
+        &self.samples[start..]
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+}
+
+impl AudioBackend for OfflineAudio {
+    fn play_tune(&mut self, tune: &PlayTune) {
+        self.mixer.cue(tune);
+    }
+
+    fn clear(&mut self) {
+        self.mixer.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::audio::{EnvSpec, Note, Waveform};
+
+    fn beep(channel: usize, delay: f64) -> PlayTune {
+        PlayTune(channel, vec![(
+            Duration::from_secs_f64(delay),
+            Note::Wave { pitch: 10.0, envelope: EnvSpec::vols(vec![(0.0, 1.0), (1.0, 1.0)]), waveform: Waveform::Pulse(0.5) },
+        )])
+    }
+
+    fn loud(samples: &[f32]) -> usize {
+        samples.iter().filter(|sample| sample.abs() > 0.5).count()
+    }
+
+    #[test]
+    fn should_be_silent_with_nothing_cued() {
+        let mut audio = OfflineAudio::new(100);
+
+        assert_eq!(audio.render(&Duration::from_secs(1)), &[0.0; 100][..]);
+    }
+
+    #[test]
+    fn should_start_notes_after_their_delay() {
+        let mut audio = OfflineAudio::new(100);
+        audio.play_tune(&beep(0, 0.5));
+
+        assert_eq!(loud(audio.render(&Duration::from_millis(500))), 0);
+        assert_eq!(loud(audio.render(&Duration::from_millis(500))), 50);
+        assert_eq!(audio.samples().len(), 100);
+    }
+
+    #[test]
+    fn should_time_delays_from_when_the_tune_was_cued() {
+        let mut audio = OfflineAudio::new(100);
+        audio.render(&Duration::from_secs(2));
+        audio.play_tune(&beep(1, 0.25));
+
+        assert_eq!(loud(audio.render(&Duration::from_millis(250))), 0);
+        assert!(loud(audio.render(&Duration::from_millis(250))) > 0);
+    }
+
+    #[test]
+    fn should_drop_cued_notes_when_cleared() {
+        let mut audio = OfflineAudio::new(100);
+        audio.play_tune(&beep(0, 0.1));
+        audio.clear();
+
+        assert_eq!(loud(audio.render(&Duration::from_secs(1))), 0);
+    }
+}
@@ -0,0 +1,53 @@
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+
+use crate::error::EngineError;
+
+use super::audio::{Mixer, PlayTune};
+use super::backend::AudioBackend;
+
+const FREQ: i32 = 48000;
+const BUFFER_SAMPLES: u16 = 1024;
+
+pub struct MixerCallback(Mixer);
+
+impl AudioCallback for MixerCallback {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        let MixerCallback(mixer) = self;
+        mixer.mix(out);
+    }
+}
+
+// plays through SDL's default output, which asks the mixer for each buffer as it needs it
+pub struct SdlAudio {
+    device: AudioDevice<MixerCallback>,
+}
+
+pub fn initialise_audio(sdl_context: &sdl2::Sdl) -> Result<SdlAudio, EngineError> {
+    let audio_subsystem = sdl_context.audio().map_err(EngineError::Audio)?;
+
+    let desired_spec = AudioSpecDesired {
+        freq: Some(FREQ),
+        channels: Some(1),  // mono
+        samples: Some(BUFFER_SAMPLES)
+    };
+
+    let device = audio_subsystem.open_playback(None, &desired_spec, |spec| MixerCallback(Mixer::new(spec.freq)))
+        .map_err(EngineError::Audio)?;
+
+    device.resume();
+    Ok(SdlAudio { device })
+}
+
+impl AudioBackend for SdlAudio {
+    fn play_tune(&mut self, tune: &PlayTune) {
+        let MixerCallback(mixer) = &mut *self.device.lock();
+        mixer.cue(tune);
+    }
+
+    fn clear(&mut self) {
+        let MixerCallback(mixer) = &mut *self.device.lock();
+        mixer.clear();
+    }
+}
@@ -1,4 +1,6 @@
 pub mod audio;
+pub mod backend;
+pub mod device;
 pub mod instrument;
 pub mod notes;
-pub mod tempo;
\ No newline at end of file
+pub mod tempo;
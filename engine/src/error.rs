@@ -0,0 +1,36 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineError {
+    Asset(String),
+    Render(String),
+    Audio(String),
+    Input(String),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::Asset(message) => write!(f, "asset error: {message}"),
+            EngineError::Render(message) => write!(f, "render error: {message}"),
+            EngineError::Audio(message) => write!(f, "audio error: {message}"),
+            EngineError::Input(message) => write!(f, "input error: {message}"),
+        }
+    }
+}
+
+impl Error for EngineError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_describe_the_failing_subsystem() {
+        assert_eq!(EngineError::Asset("missing.png".to_string()).to_string(), "asset error: missing.png");
+        assert_eq!(EngineError::Render("lost context".to_string()).to_string(), "render error: lost context");
+        assert_eq!(EngineError::Audio("no device".to_string()).to_string(), "audio error: no device");
+        assert_eq!(EngineError::Input("no pump".to_string()).to_string(), "input error: no pump");
+    }
+}
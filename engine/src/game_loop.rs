@@ -1,10 +1,17 @@
+use std::thread;
 use std::time::Instant;
 
+use sdl2::event::{Event as SdlEvent, WindowEvent};
+use sdl2::mouse::MouseButton;
 use sdl2::EventPump;
 
 use component_derive::Event;
 
+use crate::error::EngineError;
+use crate::graphics::capture::{save_gif, save_png, Captured, SaveRecording, TakeScreenshot};
+use crate::graphics::display::{Displayed, PointerMoved, PointerPressed};
 use crate::events::*;
+use crate::profiler::{DumpProfile, Profiled, StageTimed, ToggleProfilerOverlay};
 use crate as engine;
 use entity;
 
@@ -14,19 +21,22 @@ pub struct Cleanup;
 #[derive(Event)]
 pub struct CascadeInputs;
 
+#[derive(Event)]
+pub struct Quit;
+
 pub trait GameLoop<'a, R>
 {
-    fn render(&self, _renderer: &mut R) -> Result<(), String> {
+    fn render(&self, _renderer: &mut R) -> Result<(), EngineError> {
         Ok(())
     }
- 
-    fn event(&mut self, _event: &Event, _events: &mut Events) -> Result<(), String> {
+
+    fn event(&mut self, _event: &Event, _events: &mut Events) -> Result<(), EngineError> {
         Ok(())
     }
 }
 
-pub fn run_game_loop<'a, R, G>(mut game: G, renderer: &mut R, sdl_events: &mut EventPump, updates_per_frame: u32, mut events: Events) -> Result<(), String> 
-where G: GameLoop<'a, R>
+pub fn run_game_loop<'a, R, G>(mut game: G, renderer: &mut R, sdl_events: &mut EventPump, updates_per_frame: u32, mut events: Events) -> Result<(), EngineError>
+where G: GameLoop<'a, R>, R: Profiled + Captured + Displayed
 {
     let mut last_frame = Instant::now();
     let cleanup = Event::new(Cleanup);
@@ -37,18 +47,21 @@ where G: GameLoop<'a, R>
         }
         events.fire(CascadeInputs);
 
-        while let Some(event) = events.pop() {
-            game.event(&event, &mut events)?;
+        if dispatch_events(&mut game, renderer, &mut events)? {
+            return Ok(());
         }
+        renderer.profiler().record("events", this_frame.elapsed());
 
         for _ in 0..updates_per_frame {
+            let update_start = Instant::now();
             let update_duration = this_frame.duration_since(last_frame).div_f64(updates_per_frame as f64);
             events.elapse("game", &update_duration);
             events.fire(update_duration);
 
-            while let Some(event) = events.pop() {
-                game.event(&event, &mut events)?;
+            if dispatch_events(&mut game, renderer, &mut events)? {
+                return Ok(());
             }
+            renderer.profiler().record("update", update_start.elapsed());
         }
 
         game.event(&cleanup, &mut events)?;
@@ -59,3 +72,71 @@ where G: GameLoop<'a, R>
     }
 }
 
+fn dispatch_events<'a, R, G>(game: &mut G, renderer: &mut R, events: &mut Events) -> Result<bool, EngineError>
+where G: GameLoop<'a, R>, R: Profiled + Captured + Displayed
+{
+    while let Some(event) = events.pop() {
+        game.event(&event, events)?;
+        if event.unwrap::<Quit>().is_some() {
+            return Ok(true);
+        }
+        profiler_events(&event, renderer);
+        capture_events(&event, renderer);
+        display_events(&event, renderer, events);
+    }
+    Ok(false)
+}
+
+fn profiler_events<R: Profiled>(event: &Event, renderer: &mut R) {
+    event.apply(|StageTimed(stage, duration)| renderer.profiler().record(stage, *duration));
+    event.apply(|ToggleProfilerOverlay| renderer.profiler().overlay = !renderer.profiler().overlay);
+    event.apply(|DumpProfile(path)| {
+        if let Err(err) = renderer.profiler().dump_csv(path) {
+            eprintln!("Could not write profile to {path}: {err}");
+        }
+    });
+}
+
+fn display_events<R: Displayed>(event: &Event, renderer: &mut R, events: &mut Events) {
+    match event.unwrap() {
+        Some(SdlEvent::Window { win_event: WindowEvent::SizeChanged(..), .. }) => {
+            if let Err(err) = renderer.window_resized() {
+                eprintln!("Could not rescale to the new window size: {err}");
+            }
+        }
+        Some(SdlEvent::MouseMotion { x, y, .. }) => {
+            if let Some((x, y)) = renderer.window_to_view(*x, *y) {
+                events.fire(PointerMoved(x, y));
+            }
+        }
+        Some(SdlEvent::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. }) => {
+            if let Some((x, y)) = renderer.window_to_view(*x, *y) {
+                events.fire(PointerPressed(x, y));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn capture_events<R: Captured>(event: &Event, renderer: &mut R) {
+    event.apply(|TakeScreenshot(path)| {
+        if let Err(err) = renderer.screenshot().and_then(|frame| save_png(&frame, path)) {
+            eprintln!("Could not save screenshot to {path}: {err}");
+        }
+    });
+    event.apply(|SaveRecording(path)| {
+        let frames = renderer.recorder().frames();
+        if frames.is_empty() {
+            eprintln!("Nothing recorded to save to {path}; start with --record to keep the last few seconds");
+            return;
+        }
+        let delay = renderer.recorder().interval();
+        let path = path.clone();
+        // encoding takes a while, so keep it off the game loop
+        thread::spawn(move || {
+            if let Err(err) = save_gif(&frames, delay, &path) {
+                eprintln!("Could not save recording to {path}: {err}");
+            }
+        });
+    });
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use super::sprite::Sprite;
+
+// a looping sequence of frames driven by a shared clock, so every copy stays in step without its own state
+#[derive(Clone)]
+pub struct Animation {
+    frames: Vec<(Sprite, f64)>,
+    length: f64,
+}
+
+impl Animation {
+    pub fn new(frames: Vec<(Sprite, Duration)>) -> Self {
+        let frames: Vec<(Sprite, f64)> = frames.into_iter()
+            .map(|(sprite, duration)| (sprite, duration.as_secs_f64()))
+            .collect();
+        let length = frames.iter().map(|(_, duration)| duration).sum();
+        Animation { frames, length }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+
+    pub fn frame_at(&self, time: f64) -> Option<&Sprite> {
+        if self.length <= 0.0 {
+            return self.frames.first().map(|(sprite, _)| sprite);
+        }
+        let mut remaining = time.rem_euclid(self.length);
+        for (sprite, duration) in &self.frames {
+            if remaining < *duration {
+                return Some(sprite);
+            }
+            remaining -= duration;
+        }
+        self.frames.last().map(|(sprite, _)| sprite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::layer::Layer;
+
+    fn shimmer() -> Animation {
+        Animation::new(vec![
+            (Sprite::new(0, 3, Layer::Tiles, "More"), Duration::from_millis(100)),
+            (Sprite::new(1, 3, Layer::Tiles, "More"), Duration::from_millis(300)),
+        ])
+    }
+
+    fn frame(animation: &Animation, time: f64) -> Option<i32> {
+        animation.frame_at(time).map(|sprite| sprite.x)
+    }
+
+    #[test]
+    fn should_show_each_frame_for_its_duration() {
+        let animation = shimmer();
+
+        assert_eq!(frame(&animation, 0.0), Some(0));
+        assert_eq!(frame(&animation, 0.099), Some(0));
+        assert_eq!(frame(&animation, 0.1), Some(1));
+        assert_eq!(frame(&animation, 0.399), Some(1));
+    }
+
+    #[test]
+    fn should_loop() {
+        let animation = shimmer();
+
+        assert!((animation.length() - 0.4).abs() < 1e-9);
+        assert_eq!(frame(&animation, 0.45), Some(0));
+        assert_eq!(frame(&animation, 4.25), Some(1));
+    }
+
+    #[test]
+    fn should_handle_empty_and_instant_animations() {
+        assert_eq!(frame(&Animation::new(vec![]), 1.0), None);
+        assert_eq!(frame(&Animation::new(vec![(Sprite::new(2, 0, Layer::Tiles, "More"), Duration::ZERO)]), 1.0), Some(2));
+    }
+}
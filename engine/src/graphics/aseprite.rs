@@ -0,0 +1,603 @@
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use flate2::read::ZlibDecoder;
+use image::{Rgba, RgbaImage};
+
+use crate::error::EngineError;
+
+use super::frames::{Frame, SpriteFrames};
+use super::palette::Palette;
+
+const FILE_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const HEADER_SIZE: usize = 128;
+const FRAME_HEADER_SIZE: usize = 16;
+const CHUNK_HEADER_SIZE: usize = 6;
+
+const OLD_PALETTE_CHUNK: u16 = 0x0004;
+const LAYER_CHUNK: u16 = 0x2004;
+const CEL_CHUNK: u16 = 0x2005;
+const TAGS_CHUNK: u16 = 0x2018;
+const PALETTE_CHUNK: u16 = 0x2019;
+
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_BACKGROUND: u16 = 8;
+const GROUP_LAYER: u16 = 1;
+
+const RAW_CEL: u16 = 0;
+const LINKED_CEL: u16 = 1;
+const COMPRESSED_CEL: u16 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColourDepth {
+    Rgba,
+    Greyscale,
+    Indexed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+// a layer is only visible if every group it's in is too; blend modes other than normal are drawn as normal
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsepriteLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: u8,
+    pub group: bool,
+    pub background: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cel {
+    pub layer: usize,
+    pub x: i32,
+    pub y: i32,
+    pub opacity: u8,
+    pub image: RgbaImage,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsepriteFrame {
+    pub duration: Duration,
+    pub cels: Vec<Cel>,
+}
+
+// a named run of frames, from and to inclusive
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: LoopDirection,
+}
+
+impl Tag {
+    // the frames in the order they're played through once, ping-pongs there and back without repeating the ends
+    pub fn frame_order(&self) -> Vec<usize> {
+        let forward: Vec<usize> = (self.from..=self.to).collect();
+        let backward: Vec<usize> = forward.iter().rev().cloned().collect();
+        let there_and_back = |there: &[usize], back: &[usize]| {
+            there.iter().chain(back.iter().skip(1).take(back.len().saturating_sub(2))).cloned().collect()
+        };
+        match self.direction {
+            LoopDirection::Forward => forward,
+            LoopDirection::Reverse => backward,
+            LoopDirection::PingPong => there_and_back(&forward, &backward),
+            LoopDirection::PingPongReverse => there_and_back(&backward, &forward),
+        }
+    }
+}
+
+// an .aseprite file as saved by the editor, so sheets and animations can be built from it at load time
+// without exporting them by hand first
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aseprite {
+    pub width: u32,
+    pub height: u32,
+    pub depth: ColourDepth,
+    pub layers: Vec<AsepriteLayer>,
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<Tag>,
+    pub palette: Palette,
+}
+
+// a cel as it's read, before the palette it might need has been
+enum RawCel {
+    Pixels { layer: usize, x: i32, y: i32, opacity: u8, width: u32, height: u32, pixels: Vec<u8> },
+    Linked { layer: usize, frame: usize },
+}
+
+impl Aseprite {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, EngineError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|err| EngineError::Asset(format!("{}: {err}", path.display())))?;
+        Aseprite::parse(&bytes).map_err(|err| match err {
+            EngineError::Asset(message) => EngineError::Asset(format!("{}: {message}", path.display())),
+            other => other,
+        })
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, EngineError> {
+        let mut header = Reader::new(bytes);
+        header.skip(4)?;
+        if header.word()? != FILE_MAGIC {
+            return Err(EngineError::Asset("not an aseprite file".to_string()));
+        }
+        let frame_count = header.word()? as usize;
+        let width = header.word()? as u32;
+        let height = header.word()? as u32;
+        let depth = match header.word()? {
+            32 => ColourDepth::Rgba,
+            16 => ColourDepth::Greyscale,
+            8 => ColourDepth::Indexed,
+            other => return Err(EngineError::Asset(format!("unknown colour depth {other}"))),
+        };
+        header.skip(14)?;
+        let transparent_index = header.byte()?;
+
+        let mut layers: Vec<AsepriteLayer> = Vec::new();
+        let mut group_visibility: Vec<bool> = Vec::new();
+        let mut tags = Vec::new();
+        let mut palette: Vec<Rgba<u8>> = Vec::new();
+        let mut old_palette: Vec<Rgba<u8>> = Vec::new();
+        let mut raw_frames: Vec<(Duration, Vec<RawCel>)> = Vec::new();
+
+        let mut offset = HEADER_SIZE;
+        for _ in 0..frame_count {
+            let mut frame = Reader::new(bytes.get(offset..).ok_or_else(truncated)?);
+            let frame_size = frame.dword()? as usize;
+            if frame.word()? != FRAME_MAGIC {
+                return Err(EngineError::Asset(format!("frame {} is corrupt", raw_frames.len())));
+            }
+            let old_chunk_count = frame.word()? as usize;
+            let duration = Duration::from_millis(frame.word()? as u64);
+            frame.skip(2)?;
+            let chunk_count = match frame.dword()? as usize {
+                0 => old_chunk_count,
+                count => count,
+            };
+
+            let mut cels = Vec::new();
+            let mut chunk_offset = FRAME_HEADER_SIZE;
+            for _ in 0..chunk_count {
+                let mut chunk = Reader::new(frame.bytes.get(chunk_offset..).ok_or_else(truncated)?);
+                let chunk_size = chunk.dword()? as usize;
+                let chunk_type = chunk.word()?;
+                let mut body = Reader::new(chunk.bytes.get(CHUNK_HEADER_SIZE..chunk_size).ok_or_else(truncated)?);
+                match chunk_type {
+                    LAYER_CHUNK => {
+                        let flags = body.word()?;
+                        let layer_type = body.word()?;
+                        let child_level = body.word()? as usize;
+                        body.skip(6)?;
+                        let opacity = body.byte()?;
+                        body.skip(3)?;
+                        let name = body.string()?;
+                        group_visibility.truncate(child_level);
+                        let visible = flags & LAYER_VISIBLE != 0 && group_visibility.iter().all(|visible| *visible);
+                        let group = layer_type == GROUP_LAYER;
+                        if group {
+                            group_visibility.push(visible);
+                        }
+                        layers.push(AsepriteLayer { name, visible, opacity, group, background: flags & LAYER_BACKGROUND != 0 });
+                    }
+                    CEL_CHUNK => {
+                        let layer = body.word()? as usize;
+                        let x = body.short()? as i32;
+                        let y = body.short()? as i32;
+                        let opacity = body.byte()?;
+                        let cel_type = body.word()?;
+                        body.skip(7)?;
+                        match cel_type {
+                            RAW_CEL | COMPRESSED_CEL => {
+                                let width = body.word()? as u32;
+                                let height = body.word()? as u32;
+                                let pixels = if cel_type == RAW_CEL {
+                                    body.rest().to_vec()
+                                } else {
+                                    let mut pixels = Vec::new();
+                                    ZlibDecoder::new(body.rest()).read_to_end(&mut pixels)
+                                        .map_err(|err| EngineError::Asset(format!("couldn't inflate a cel: {err}")))?;
+                                    pixels
+                                };
+                                cels.push(RawCel::Pixels { layer, x, y, opacity, width, height, pixels });
+                            }
+                            LINKED_CEL => cels.push(RawCel::Linked { layer, frame: body.word()? as usize }),
+                            // tilemaps aren't drawn
+                            _ => {}
+                        }
+                    }
+                    TAGS_CHUNK => {
+                        let count = body.word()?;
+                        body.skip(8)?;
+                        for _ in 0..count {
+                            let from = body.word()? as usize;
+                            let to = body.word()? as usize;
+                            let direction = match body.byte()? {
+                                1 => LoopDirection::Reverse,
+                                2 => LoopDirection::PingPong,
+                                3 => LoopDirection::PingPongReverse,
+                                _ => LoopDirection::Forward,
+                            };
+                            body.skip(12)?;
+                            tags.push(Tag { name: body.string()?, from, to, direction });
+                        }
+                    }
+                    PALETTE_CHUNK => {
+                        let size = body.dword()? as usize;
+                        let first = body.dword()? as usize;
+                        let last = body.dword()? as usize;
+                        body.skip(8)?;
+                        palette.resize(size.max(palette.len()), Rgba([0, 0, 0, 0]));
+                        for index in first..=last {
+                            let flags = body.word()?;
+                            let colour = Rgba([body.byte()?, body.byte()?, body.byte()?, body.byte()?]);
+                            if flags & 1 != 0 {
+                                body.string()?;
+                            }
+                            if let Some(entry) = palette.get_mut(index) {
+                                *entry = colour;
+                            }
+                        }
+                    }
+                    // only older files have nothing but this
+                    OLD_PALETTE_CHUNK => {
+                        let packets = body.word()?;
+                        let mut index = 0;
+                        for _ in 0..packets {
+                            index += body.byte()? as usize;
+                            let count = match body.byte()? {
+                                0 => 256,
+                                count => count as usize,
+                            };
+                            old_palette.resize(old_palette.len().max(index + count), Rgba([0, 0, 0, 255]));
+                            for _ in 0..count {
+                                old_palette[index] = Rgba([body.byte()?, body.byte()?, body.byte()?, 255]);
+                                index += 1;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                chunk_offset += chunk_size;
+            }
+            raw_frames.push((duration, cels));
+            offset += frame_size;
+        }
+
+        let palette = if palette.is_empty() { old_palette } else { palette };
+        let colour = |pixel: &[u8], background: bool| match depth {
+            ColourDepth::Rgba => Rgba([pixel[0], pixel[1], pixel[2], pixel[3]]),
+            ColourDepth::Greyscale => Rgba([pixel[0], pixel[0], pixel[0], pixel[1]]),
+            ColourDepth::Indexed if pixel[0] == transparent_index && !background => Rgba([0, 0, 0, 0]),
+            ColourDepth::Indexed => palette.get(pixel[0] as usize).copied().unwrap_or(Rgba([0, 0, 0, 0])),
+        };
+        let bytes_per_pixel = match depth {
+            ColourDepth::Rgba => 4,
+            ColourDepth::Greyscale => 2,
+            ColourDepth::Indexed => 1,
+        };
+
+        let mut frames: Vec<AsepriteFrame> = Vec::new();
+        for (duration, raw_cels) in &raw_frames {
+            let mut cels = Vec::new();
+            for raw_cel in raw_cels {
+                match raw_cel {
+                    RawCel::Pixels { layer, x, y, opacity, width, height, pixels } => {
+                        if pixels.len() < (width * height) as usize * bytes_per_pixel {
+                            return Err(EngineError::Asset(format!("a cel in frame {} is short of pixels", frames.len())));
+                        }
+                        let background = layers.get(*layer).map_or(false, |layer| layer.background);
+                        let image = RgbaImage::from_fn(*width, *height, |px, py| {
+                            let start = (py * width + px) as usize * bytes_per_pixel;
+                            colour(&pixels[start..start + bytes_per_pixel], background)
+                        });
+                        cels.push(Cel { layer: *layer, x: *x, y: *y, opacity: *opacity, image });
+                    }
+                    RawCel::Linked { layer, frame } => {
+                        let linked = frames.get(*frame)
+                            .and_then(|frame| frame.cels.iter().find(|cel| cel.layer == *layer))
+                            .ok_or_else(|| EngineError::Asset(format!("a cel in frame {} links to nothing", frames.len())))?;
+                        cels.push(linked.clone());
+                    }
+                }
+            }
+            cels.sort_by_key(|cel| cel.layer);
+            frames.push(AsepriteFrame { duration: *duration, cels });
+        }
+
+        Ok(Aseprite { width, height, depth, layers, frames, tags, palette: Palette(palette) })
+    }
+
+    // every visible layer flattened, bottom up
+    pub fn frame_image(&self, frame: usize) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width, self.height);
+        for cel in self.frames.get(frame).map_or(&[][..], |frame| &frame.cels) {
+            let Some(layer) = self.layers.get(cel.layer) else { continue };
+            if !layer.visible || layer.group {
+                continue;
+            }
+            let opacity = cel.opacity as u32 * layer.opacity as u32;
+            for (px, py, pixel) in cel.image.enumerate_pixels() {
+                let (x, y) = (cel.x + px as i32, cel.y + py as i32);
+                if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                    continue;
+                }
+                blend(image.get_pixel_mut(x as u32, y as u32), pixel, opacity);
+            }
+        }
+        image
+    }
+
+    // all the frames side by side, to load as a sheet
+    pub fn sheet_image(&self) -> RgbaImage {
+        let mut sheet = RgbaImage::new(self.width * self.frames.len().max(1) as u32, self.height);
+        for frame in 0..self.frames.len() {
+            image::imageops::replace(&mut sheet, &self.frame_image(frame), frame as u32 * self.width, 0);
+        }
+        sheet
+    }
+
+    // names the frames of each tag on the sheet made by sheet_image, "tag/0" onwards in the order they play,
+    // and makes the tag an animation of them; each frame is as many cells of the sheet as it covers
+    pub fn add_frames(&self, frames: &mut SpriteFrames, sheet: &str, cell_width: u32, cell_height: u32) {
+        let (columns, rows) = ((self.width / cell_width).max(1), (self.height / cell_height).max(1));
+        for tag in &self.tags {
+            let steps = tag.frame_order().into_iter().enumerate().map(|(step, index)| {
+                let name = format!("{}/{step}", tag.name);
+                let frame = Frame { sheet: sheet.to_string(), x: (index as u32 * columns) as i32, y: 0, width: columns, height: rows };
+                frames.add_frame(&name, frame);
+                (name, self.frames.get(index).map_or(Duration::ZERO, |frame| frame.duration))
+            }).collect();
+            frames.add_animation(&tag.name, steps);
+        }
+    }
+}
+
+// the source pixel drawn over the destination, its alpha scaled by an opacity out of 255 * 255
+fn blend(destination: &mut Rgba<u8>, Rgba([r, g, b, a]): &Rgba<u8>, opacity: u32) {
+    let alpha = *a as u32 * opacity / (255 * 255);
+    if alpha == 0 {
+        return;
+    }
+    let Rgba([dr, dg, db, da]) = *destination;
+    let under = da as u32 * (255 - alpha) / 255;
+    let out = alpha + under;
+    let mix = |source: u8, below: u8| ((source as u32 * alpha + below as u32 * under) / out) as u8;
+    *destination = Rgba([mix(*r, dr), mix(*g, dg), mix(*b, db), out as u8]);
+}
+
+fn truncated() -> EngineError {
+    EngineError::Asset("the file ends too soon".to_string())
+}
+
+// little-endian fields read off the front of a slice
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], EngineError> {
+        let taken = self.bytes.get(self.position..self.position + count).ok_or_else(truncated)?;
+        self.position += count;
+        Ok(taken)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), EngineError> {
+        self.take(count).map(|_| ())
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.position.min(self.bytes.len())..]
+    }
+
+    fn byte(&mut self) -> Result<u8, EngineError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, EngineError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn short(&mut self) -> Result<i16, EngineError> {
+        self.word().map(|word| word as i16)
+    }
+
+    fn dword(&mut self) -> Result<u32, EngineError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, EngineError> {
+        let length = self.word()? as usize;
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+    use crate::graphics::layer::Layer;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    fn string(text: &str) -> Vec<u8> {
+        [&(text.len() as u16).to_le_bytes()[..], text.as_bytes()].concat()
+    }
+
+    fn chunk(kind: u16, body: Vec<u8>) -> Vec<u8> {
+        [&((body.len() + CHUNK_HEADER_SIZE) as u32).to_le_bytes()[..], &kind.to_le_bytes(), &body].concat()
+    }
+
+    fn layer(name: &str, flags: u16, layer_type: u16, child_level: u16, opacity: u8) -> Vec<u8> {
+        let fields = [flags, layer_type, child_level, 0, 0, 0].iter().flat_map(|word| word.to_le_bytes()).collect::<Vec<u8>>();
+        chunk(LAYER_CHUNK, [fields, vec![opacity, 0, 0, 0], string(name)].concat())
+    }
+
+    fn cel(layer: u16, (x, y): (i16, i16), cel_type: u16, payload: Vec<u8>) -> Vec<u8> {
+        let fields = [&layer.to_le_bytes()[..], &x.to_le_bytes(), &y.to_le_bytes(), &[255], &cel_type.to_le_bytes(), &[0; 7]].concat();
+        chunk(CEL_CHUNK, [fields, payload].concat())
+    }
+
+    fn pixels(width: u16, height: u16, colour: [u8; 4]) -> Vec<u8> {
+        [&width.to_le_bytes()[..], &height.to_le_bytes(), &colour.repeat((width * height) as usize)].concat()
+    }
+
+    fn compressed(width: u16, height: u16, colour: [u8; 4]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&colour.repeat((width * height) as usize)).unwrap();
+        [&width.to_le_bytes()[..], &height.to_le_bytes(), &encoder.finish().unwrap()].concat()
+    }
+
+    fn tags(tags: &[(u16, u16, u8, &str)]) -> Vec<u8> {
+        let entries = tags.iter().flat_map(|(from, to, direction, name)| {
+            [&from.to_le_bytes()[..], &to.to_le_bytes(), &[*direction], &[0; 12], &string(name)].concat()
+        });
+        chunk(TAGS_CHUNK, [&(tags.len() as u16).to_le_bytes()[..], &[0; 8]].concat().into_iter().chain(entries).collect())
+    }
+
+    fn file(width: u16, height: u16, frames: &[(u16, Vec<Vec<u8>>)]) -> Vec<u8> {
+        let mut header = [&[0; 4][..], &FILE_MAGIC.to_le_bytes(), &(frames.len() as u16).to_le_bytes(),
+            &width.to_le_bytes(), &height.to_le_bytes(), &32u16.to_le_bytes()].concat();
+        header.resize(HEADER_SIZE, 0);
+        let frames = frames.iter().flat_map(|(duration, chunks)| {
+            let body = chunks.concat();
+            [&((body.len() + FRAME_HEADER_SIZE) as u32).to_le_bytes()[..], &FRAME_MAGIC.to_le_bytes(), &[0; 2],
+                &duration.to_le_bytes(), &[0; 2], &(chunks.len() as u32).to_le_bytes(), &body].concat()
+        });
+        header.into_iter().chain(frames).collect()
+    }
+
+    // a hidden sketch layer under two frames of a red square, the second linked to the first,
+    // and a blue one that's only in the last frame
+    fn sprite() -> Aseprite {
+        Aseprite::parse(&file(4, 4, &[
+            (100, vec![
+                layer("Sketch", 0, 0, 0, 255),
+                layer("Body", LAYER_VISIBLE, 0, 0, 255),
+                tags(&[(0, 2, 0, "walk"), (0, 2, 2, "bob")]),
+                cel(0, (0, 0), RAW_CEL, pixels(4, 4, BLUE)),
+                cel(1, (1, 1), COMPRESSED_CEL, compressed(2, 2, RED)),
+            ]),
+            (150, vec![cel(1, (0, 0), LINKED_CEL, 0u16.to_le_bytes().to_vec())]),
+            (200, vec![cel(1, (2, 0), RAW_CEL, pixels(2, 4, BLUE))]),
+        ])).unwrap()
+    }
+
+    #[test]
+    fn should_read_frames_layers_and_tags() {
+        let sprite = sprite();
+
+        assert_eq!((sprite.width, sprite.height, sprite.depth), (4, 4, ColourDepth::Rgba));
+        assert_eq!(sprite.layers.iter().map(|layer| (layer.name.as_str(), layer.visible)).collect::<Vec<_>>(), vec![("Sketch", false), ("Body", true)]);
+        assert_eq!(sprite.frames.iter().map(|frame| frame.duration.as_millis()).collect::<Vec<_>>(), vec![100, 150, 200]);
+        assert_eq!(sprite.tags[0], Tag { name: "walk".to_string(), from: 0, to: 2, direction: LoopDirection::Forward });
+    }
+
+    #[test]
+    fn should_flatten_only_visible_layers() {
+        let sprite = sprite();
+        let first = sprite.frame_image(0);
+
+        assert_eq!(first.get_pixel(1, 1), &Rgba(RED));
+        assert_eq!(first.get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+        assert_eq!(sprite.frame_image(1), first);
+        assert_eq!(sprite.frame_image(2).get_pixel(3, 3), &Rgba(BLUE));
+        assert_eq!(sprite.frame_image(2).get_pixel(1, 1), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_hide_layers_in_hidden_groups() {
+        let sprite = Aseprite::parse(&file(1, 1, &[(100, vec![
+            layer("Hidden", 0, GROUP_LAYER, 0, 255),
+            layer("Inside", LAYER_VISIBLE, 0, 1, 255),
+            layer("Outside", LAYER_VISIBLE, 0, 0, 255),
+        ])])).unwrap();
+
+        assert_eq!(sprite.layers.iter().map(|layer| layer.visible).collect::<Vec<_>>(), vec![false, false, true]);
+    }
+
+    #[test]
+    fn should_blend_by_cel_and_layer_opacity() {
+        let sprite = Aseprite::parse(&file(1, 1, &[(100, vec![
+            layer("Under", LAYER_VISIBLE, 0, 0, 255),
+            layer("Over", LAYER_VISIBLE, 0, 0, 128),
+            cel(0, (0, 0), RAW_CEL, pixels(1, 1, BLUE)),
+            cel(1, (0, 0), RAW_CEL, pixels(1, 1, RED)),
+        ])])).unwrap();
+
+        assert_eq!(sprite.frame_image(0).get_pixel(0, 0), &Rgba([128, 0, 127, 255]));
+    }
+
+    #[test]
+    fn should_lay_frames_side_by_side() {
+        let sheet = sprite().sheet_image();
+
+        assert_eq!(sheet.dimensions(), (12, 4));
+        assert_eq!(sheet.get_pixel(5, 1), &Rgba(RED));
+        assert_eq!(sheet.get_pixel(11, 3), &Rgba(BLUE));
+    }
+
+    #[test]
+    fn should_play_tags_in_their_direction() {
+        let tag = |direction| Tag { name: "t".to_string(), from: 2, to: 5, direction }.frame_order();
+
+        assert_eq!(tag(LoopDirection::Forward), vec![2, 3, 4, 5]);
+        assert_eq!(tag(LoopDirection::Reverse), vec![5, 4, 3, 2]);
+        assert_eq!(tag(LoopDirection::PingPong), vec![2, 3, 4, 5, 4, 3]);
+        assert_eq!(tag(LoopDirection::PingPongReverse), vec![5, 4, 3, 2, 3, 4]);
+    }
+
+    #[test]
+    fn should_turn_tags_into_animations() {
+        let mut frames = SpriteFrames::new();
+        sprite().add_frames(&mut frames, "Walker", 2, 2);
+
+        assert_eq!(frames.frame("walk/2").unwrap(), &Frame { sheet: "Walker".to_string(), x: 4, y: 0, width: 2, height: 2 });
+        let bob = frames.animation("bob", Layer::Actors).unwrap();
+        assert!((bob.length() - 0.6).abs() < 1e-9);
+        assert_eq!(bob.frame_at(0.5).map(|sprite| sprite.x), Some(2));
+    }
+
+    #[test]
+    fn should_read_the_palette_files() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/graphics/pandamonium_palette.aseprite");
+        let sprite = Aseprite::open(path).unwrap();
+        let Palette(colours) = &sprite.palette;
+
+        assert_eq!((sprite.width, sprite.height, sprite.depth), (16, 2, ColourDepth::Indexed));
+        assert_eq!(colours.len(), 28);
+        assert_eq!(colours[9], Rgba([42, 75, 120, 255]));
+        assert_eq!(sprite.frame_image(0).get_pixel(9, 0), &Rgba([42, 75, 120, 255]));
+        assert_eq!(sprite.frame_image(0).get_pixel(11, 1), &Rgba([255, 115, 0, 255]));
+    }
+
+    #[test]
+    fn should_reject_what_isnt_an_aseprite_file() {
+        let error = |bytes: &[u8]| Aseprite::parse(bytes).err().map(|err| err.to_string());
+
+        assert_eq!(error(&[0; 200]), Some("asset error: not an aseprite file".to_string()));
+        assert_eq!(error(&file(4, 4, &[(100, vec![cel(0, (0, 0), RAW_CEL, pixels(4, 4, BLUE))])])[..200]),
+            Some("asset error: the file ends too soon".to_string()));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use image::RgbaImage;
+
+// left empty around every sheet, so nothing scaled or rotated picks up its neighbour's edge
+const PADDING: u32 = 1;
+
+// sheets packed together into as few pages as will hold them, so drawing from one sheet after another
+// doesn't mean switching textures. Each sheet is kept whole and remembers which page it went on and where
+pub struct Atlas {
+    pub pages: Vec<RgbaImage>,
+    pub placements: HashMap<String, (usize, (i32, i32))>,
+}
+
+impl Atlas {
+    // tallest first along shelves, starting a shelf when a row is full and a page when the shelves are;
+    // anything too big for a page gets one to itself
+    pub fn pack(mut sheets: Vec<(String, RgbaImage)>, page_size: u32) -> Self {
+        sheets.sort_by(|(name1, image1), (name2, image2)| image2.height().cmp(&image1.height()).then(name1.cmp(name2)));
+
+        let mut pages: Vec<RgbaImage> = Vec::new();
+        let mut placements = HashMap::new();
+        // where the next sheet goes on the last page: across, the top of the shelf, and the shelf's height
+        let mut cursor = (0, 0, 0);
+        for (name, image) in sheets {
+            let (width, height) = (image.width() + PADDING, image.height() + PADDING);
+            if width > page_size || height > page_size {
+                placements.insert(name, (pages.len(), (0, 0)));
+                pages.push(image);
+                cursor = (page_size, page_size, 0);
+                continue;
+            }
+            let (mut x, mut y, mut shelf) = cursor;
+            if x + width > page_size {
+                (x, y, shelf) = (0, y + shelf, 0);
+            }
+            if pages.is_empty() || y + height > page_size {
+                pages.push(RgbaImage::new(page_size, page_size));
+                (x, y, shelf) = (0, 0, 0);
+            }
+            let page = pages.len() - 1;
+            image::imageops::replace(&mut pages[page], &image, x, y);
+            placements.insert(name, (page, (x as i32, y as i32)));
+            cursor = (x + width, y, shelf.max(height));
+        }
+        Atlas { pages, placements }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    fn sheet(name: &str, width: u32, height: u32, level: u8) -> (String, RgbaImage) {
+        (name.to_string(), RgbaImage::from_pixel(width, height, Rgba([level, level, level, 255])))
+    }
+
+    #[test]
+    fn should_put_sheets_side_by_side_on_one_page() {
+        let atlas = Atlas::pack(vec![sheet("Small", 4, 2, 10), sheet("Tall", 3, 6, 20)], 16);
+
+        assert_eq!(atlas.pages.len(), 1);
+        assert_eq!(atlas.placements["Tall"], (0, (0, 0)));
+        assert_eq!(atlas.placements["Small"], (0, (4, 0)));
+        assert_eq!(atlas.pages[0].get_pixel(5, 1), &Rgba([10, 10, 10, 255]));
+        assert_eq!(atlas.pages[0].get_pixel(3, 0), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_start_a_shelf_when_a_row_is_full() {
+        let atlas = Atlas::pack(vec![sheet("A", 6, 4, 1), sheet("B", 6, 4, 2), sheet("C", 6, 3, 3)], 16);
+
+        assert_eq!(atlas.pages.len(), 1);
+        assert_eq!(atlas.placements["C"], (0, (0, 5)));
+    }
+
+    #[test]
+    fn should_start_a_page_when_the_shelves_are_full() {
+        let atlas = Atlas::pack(vec![sheet("A", 10, 10, 1), sheet("B", 10, 10, 2)], 16);
+
+        assert_eq!(atlas.pages.len(), 2);
+        assert_eq!(atlas.placements["B"], (1, (0, 0)));
+        assert_eq!(atlas.pages[1].get_pixel(9, 9), &Rgba([2, 2, 2, 255]));
+    }
+
+    #[test]
+    fn should_give_a_sheet_too_big_for_a_page_its_own() {
+        let atlas = Atlas::pack(vec![sheet("Huge", 40, 8, 1), sheet("Small", 2, 2, 2)], 16);
+
+        assert_eq!(atlas.pages.len(), 2);
+        assert_eq!(atlas.pages[0].dimensions(), (40, 8));
+        assert_eq!(atlas.placements["Small"], (1, (0, 0)));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::error::EngineError;
+use crate::shapes::shape::shape::Shape;
+
+use super::camera::Camera;
+use super::font::Font;
+use super::lighting::PointLight;
+use super::parallax::ParallaxLayer;
+use super::renderer::Text;
+use super::sprite::Sprite;
+use super::transition::Transition;
+use super::viewport::Viewport;
+
+pub trait RenderBackend {
+    fn set_camera(&mut self, camera: Option<&Camera>);
+
+    fn set_viewport(&mut self, viewport: Option<&Viewport>);
+
+    fn add_font(&mut self, name: &str, font: Font);
+
+    fn draw_sprite(&mut self, sprite: &Sprite, x: f64, y: f64);
+
+    fn draw_text(&mut self, text: &Text, x: f64, y: f64);
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, colour: (u8, u8, u8, u8));
+
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), colour: (u8, u8, u8, u8));
+
+    fn draw_circle(&mut self, center: (f64, f64), radius: f64, colour: (u8, u8, u8, u8));
+
+    // everything below the interface is multiplied by the light map: the ambient level, with the lights added on
+    fn set_ambient_light(&mut self, ambient: Option<(u8, u8, u8)>);
+
+    fn draw_light(&mut self, x: f64, y: f64, light: &PointLight);
+
+    fn clear(&mut self) -> Result<(), EngineError>;
+
+    fn present(&mut self) -> Result<(), EngineError>;
+
+    // flushes everything drawn so far into a held frame and starts a fresh one on top of it
+    fn hold_frame(&mut self) -> Result<(), EngineError>;
+
+    fn present_transition(&mut self, transition: &Transition) -> Result<(), EngineError>;
+
+    // each view is drawn through its own camera and kept inside its own part of the picture; afterwards the
+    // whole picture is free again for a HUD over the top of them all
+    fn draw_views<F: FnMut(&mut Self, usize)>(&mut self, views: &[(Viewport, Camera)], mut draw: F) where Self: Sized {
+        for (index, (viewport, camera)) in views.iter().enumerate() {
+            self.set_viewport(Some(viewport));
+            self.set_camera(Some(camera));
+            draw(self, index);
+        }
+        self.set_viewport(None);
+        self.set_camera(None);
+    }
+
+    fn draw_layer(&mut self, layer: &ParallaxLayer, camera: &Camera) {
+        let (dx, dy) = layer.offset;
+        for (x, y, sprite) in layer.visible_tiles(camera) {
+            self.draw_sprite(sprite, x + dx, y + dy);
+        }
+    }
+
+    fn draw_shape(&mut self, shape: &Shape, colour: (u8, u8, u8, u8)) {
+        match shape {
+            Shape::Circle(circle) => self.draw_circle(circle.center, circle.radius, colour),
+            Shape::BBox(bbox) => {
+                let corners = [(bbox.left, bbox.bottom), (bbox.right, bbox.bottom), (bbox.right, bbox.top), (bbox.left, bbox.top)];
+                for (i, corner) in corners.iter().enumerate() {
+                    self.draw_line(*corner, corners[(i + 1) % corners.len()], colour);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,379 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use super::camera::Camera;
+use super::font::{Font, DEFAULT_FONT};
+use super::layer::Layer;
+use super::lighting::{Glow, PointLight};
+use super::renderer::{align, Text};
+use super::sprite::Sprite;
+use super::viewport::Viewport;
+
+const CIRCLE_SEGMENTS: usize = 24;
+// text sits over any other interface sprites
+const TEXT_DEPTH: f64 = 1.0;
+
+pub type Line = ((i32, i32), (i32, i32), (u8, u8, u8, u8));
+pub type Fill = ((i32, i32, u32, u32), (u8, u8, u8, u8));
+
+// the part of the picture something may be drawn in: left, bottom, width and height in pixels
+pub type Clip = (i32, i32, u32, u32);
+
+pub type Placed = (Sprite, (i32, i32), Option<Clip>);
+
+pub struct SpriteBatch {
+    layers: Vec<Vec<Placed>>,
+    fills: Vec<Fill>,
+    lines: Vec<Line>,
+    ambient: Option<(u8, u8, u8)>,
+    glows: Vec<Glow>,
+    camera: Option<Camera>,
+    viewport: Option<Viewport>,
+    tile_width: f64,
+    tile_height: f64,
+    fonts: HashMap<String, Font>,
+}
+
+impl SpriteBatch {
+    pub fn new(tile_width: u32, tile_height: u32) -> Self {
+        SpriteBatch {
+            layers: Layer::ALL.iter().map(|_| Vec::new()).collect(),
+            fills: Vec::new(),
+            lines: Vec::new(),
+            ambient: None,
+            glows: Vec::new(),
+            camera: None,
+            viewport: None,
+            tile_width: tile_width as f64,
+            tile_height: tile_height as f64,
+            fonts: HashMap::from([(DEFAULT_FONT.to_string(), Font::standard(DEFAULT_FONT, 8, 8))]),
+        }
+    }
+
+    pub fn add_font(&mut self, name: &str, font: Font) {
+        self.fonts.insert(name.to_string(), font);
+    }
+
+    pub fn text_height(&self) -> f64 {
+        self.fonts.get(DEFAULT_FONT).map_or(0.0, |font| font.line_height() as f64 / self.tile_height)
+    }
+
+    pub fn set_camera(&mut self, camera: Option<&Camera>) {
+        self.camera = camera.cloned();
+    }
+
+    // everything after this is drawn into the viewport and kept inside it, until it's set back to None
+    pub fn set_viewport(&mut self, viewport: Option<&Viewport>) {
+        self.viewport = viewport.cloned();
+    }
+
+    pub fn push_sprite(&mut self, sprite: &Sprite, x: f64, y: f64) {
+        let zoom = self.camera.as_ref().map_or(1.0, |camera| camera.zoom);
+        let clip = self.clip();
+        if zoom == 1.0 {
+            let position = self.to_pixels(x, y);
+            self.layers[sprite.layer.index()].push((sprite.clone(), position, clip));
+            return;
+        }
+        // under a zoom the sprite's pivot goes where the zoom puts it, and the sprite grows around that
+        let (pivot_x, pivot_y) = (sprite.pivot.0 * sprite.width as f64, sprite.pivot.1 * sprite.height as f64);
+        let (view_x, view_y) = self.to_view_pixels(x + pivot_x, y + pivot_y);
+        let position = ((view_x - pivot_x * self.tile_width).round() as i32, (view_y - pivot_y * self.tile_height).round() as i32);
+        let (scale_x, scale_y) = sprite.scale;
+        self.layers[sprite.layer.index()].push((sprite.clone().scaled(scale_x * zoom, scale_y * zoom), position, clip));
+    }
+
+    pub fn push_text(&mut self, text: &Text, x: f64, y: f64) {
+        // text in an unknown font falls back to the default one rather than vanishing
+        let clip = self.clip();
+        let fonts = &self.fonts;
+        let font = match fonts.get(&text.font).or_else(|| fonts.get(DEFAULT_FONT)) {
+            Some(font) => font,
+            None => return,
+        };
+        let (x, y) = self.to_view_pixels(x, y);
+        let lines = font.lines(&text.text, text.wrap.map(|wrap| (wrap * self.tile_width).round() as i32));
+        let line_height = font.line_height() as f64;
+        let block_height = lines.len() as f64 * line_height - font.line_spacing as f64;
+
+        let bottom = match (text.justification & align::BOTTOM > 0, text.justification & align::TOP > 0) {
+            (true, false) => y,
+            (false, true) => y - block_height,
+            _ => y - (block_height / 2.0),
+        };
+
+        for (line_number, line) in lines.iter().enumerate() {
+            let line_width = font.measure(line) as f64;
+            let mut current_x = match (text.justification & align::LEFT > 0, text.justification & align::RIGHT > 0) {
+                (true, false) => x,
+                (false, true) => x - line_width,
+                _ => x - (line_width / 2.0),
+            };
+            let line_y = bottom + (lines.len() - 1 - line_number) as f64 * line_height;
+
+            for (i, (index, ch)) in line.iter().enumerate() {
+                let glyph = font.glyph(*ch);
+                let style = text.style_at(*index);
+                if style.visible && style.scale > 0.0 {
+                    let sprite = Sprite::new(glyph.x, glyph.y, Layer::Ui, &font.sheet).with_depth(TEXT_DEPTH);
+                    let sprite = match style.colour {
+                        Some((r, g, b)) => sprite.tinted(r, g, b, 255),
+                        None => sprite,
+                    };
+                    let sprite = if style.scale != 1.0 { sprite.scaled(style.scale, style.scale) } else { sprite };
+                    let (dx, dy) = style.offset;
+                    let position = ((current_x + dx).round() as i32, (line_y + dy).round() as i32);
+                    self.layers[Layer::Ui.index()].push((sprite, position, clip));
+                }
+                current_x += font.advance(*ch, line.get(i + 1).map(|(_, next)| *next)) as f64;
+            }
+        }
+    }
+
+    pub fn push_fill(&mut self, x: f64, y: f64, width: f64, height: f64, colour: (u8, u8, u8, u8)) {
+        let (left, bottom) = self.to_pixels(x, y);
+        let (right, top) = self.to_pixels(x + width, y + height);
+        let (left, bottom, right, top) = match self.clip() {
+            Some((clip_x, clip_y, clip_width, clip_height)) =>
+                (left.max(clip_x), bottom.max(clip_y), right.min(clip_x + clip_width as i32), top.min(clip_y + clip_height as i32)),
+            None => (left, bottom, right, top),
+        };
+        if right > left && top > bottom {
+            self.fills.push(((left, bottom, (right - left) as u32, (top - bottom) as u32), colour));
+        }
+    }
+
+    pub fn push_line(&mut self, from: (f64, f64), to: (f64, f64), colour: (u8, u8, u8, u8)) {
+        let (from, to) = (self.to_pixels(from.0, from.1), self.to_pixels(to.0, to.1));
+        let clipped = match self.clip() {
+            Some(clip) => clip_line(from, to, clip),
+            None => Some((from, to)),
+        };
+        if let Some((from, to)) = clipped {
+            self.lines.push((from, to, colour));
+        }
+    }
+
+    // circles are outlined as a closed run of short lines, so backends only ever have to draw lines
+    pub fn push_circle(&mut self, (x, y): (f64, f64), radius: f64, colour: (u8, u8, u8, u8)) {
+        let point = |segment: usize| {
+            let (sin, cos) = (segment as f64 * std::f64::consts::TAU / CIRCLE_SEGMENTS as f64).sin_cos();
+            (x + radius * cos, y + radius * sin)
+        };
+        for segment in 0..CIRCLE_SEGMENTS {
+            self.push_line(point(segment), point(segment + 1), colour);
+        }
+    }
+
+    // without an ambient level there's no lighting at all, and lights are ignored
+    pub fn set_ambient_light(&mut self, ambient: Option<(u8, u8, u8)>) {
+        self.ambient = ambient;
+    }
+
+    pub fn push_light(&mut self, x: f64, y: f64, light: &PointLight) {
+        let zoom = self.camera.as_ref().map_or(1.0, |camera| camera.zoom);
+        let radius = (light.radius * zoom * self.tile_width).round() as u32;
+        if radius > 0 {
+            self.glows.push((self.to_pixels(x, y), radius, light.colour));
+        }
+    }
+
+    pub fn ambient_light(&self) -> Option<(u8, u8, u8)> {
+        self.ambient
+    }
+
+    pub fn glows(&self) -> &Vec<Glow> {
+        &self.glows
+    }
+
+    // fills are drawn over every sprite, and lines over them, each in the order they were pushed
+    pub fn fills(&self) -> &Vec<Fill> {
+        &self.fills
+    }
+
+    pub fn lines(&self) -> &Vec<Line> {
+        &self.lines
+    }
+
+    // each layer is sorted by depth, otherwise keeping the order things were drawn in, and comes back as runs
+    // of neighbouring sprites that share a sheet and a clip so backends can switch once per run
+    pub fn runs(&mut self) -> Vec<&[Placed]> {
+        for layer in self.layers.iter_mut() {
+            layer.sort_by(|(sprite1, _, _), (sprite2, _, _)| compare(&sprite1.depth, &sprite2.depth));
+        }
+        self.layers.iter()
+            .flat_map(|layer| layer.chunk_by(|(sprite1, _, clip1), (sprite2, _, clip2)| sprite1.same_sheet(sprite2) && clip1 == clip2))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.layers.iter_mut().for_each(|layer| layer.clear());
+        self.fills.clear();
+        self.lines.clear();
+        self.ambient = None;
+        self.glows.clear();
+    }
+
+    fn to_pixels(&self, x: f64, y: f64) -> (i32, i32) {
+        let (x, y) = self.to_view_pixels(x, y);
+        (x.round() as i32, y.round() as i32)
+    }
+
+    fn to_view_pixels(&self, x: f64, y: f64) -> (f64, f64) {
+        let (x, y) = match &self.camera {
+            Some(camera) => camera.to_view(x, y),
+            None => (x, y),
+        };
+        let (x, y) = match &self.viewport {
+            Some(viewport) => (x + viewport.x, y + viewport.y),
+            None => (x, y),
+        };
+        (x * self.tile_width, y * self.tile_height)
+    }
+
+    fn clip(&self) -> Option<Clip> {
+        self.viewport.map(|Viewport { x, y, width, height }| {
+            let (left, bottom) = ((x * self.tile_width).round() as i32, (y * self.tile_height).round() as i32);
+            let (right, top) = (((x + width) * self.tile_width).round() as i32, ((y + height) * self.tile_height).round() as i32);
+            (left, bottom, (right - left).max(0) as u32, (top - bottom).max(0) as u32)
+        })
+    }
+}
+
+// liang-barsky: cuts the line down to the part inside the clip, if there is any
+fn clip_line((x1, y1): (i32, i32), (x2, y2): (i32, i32), (left, bottom, width, height): Clip) -> Option<((i32, i32), (i32, i32))> {
+    let (dx, dy) = ((x2 - x1) as f64, (y2 - y1) as f64);
+    let (right, top) = (left + width as i32 - 1, bottom + height as i32 - 1);
+    let mut range = (0.0, 1.0);
+    for (p, q) in [(-dx, (x1 - left) as f64), (dx, (right - x1) as f64), (-dy, (y1 - bottom) as f64), (dy, (top - y1) as f64)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            range.0 = f64::max(range.0, q / p);
+        } else {
+            range.1 = f64::min(range.1, q / p);
+        }
+    }
+    if range.0 > range.1 {
+        return None;
+    }
+    let point = |t: f64| ((x1 as f64 + t * dx).round() as i32, (y1 as f64 + t * dy).round() as i32);
+    Some((point(range.0), point(range.1)))
+}
+
+fn compare(a: &f64, b: &f64) -> Ordering {
+    match a.partial_cmp(b) {
+        Some(ord) => ord,
+        None => Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheets_and_cells(batch: &mut SpriteBatch) -> Vec<Vec<(String, i32)>> {
+        batch.runs().iter()
+            .map(|run| run.iter().map(|(sprite, _, _)| (sprite.sheet_name(), sprite.x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn should_draw_layers_back_to_front() {
+        let mut batch = SpriteBatch::new(1, 1);
+        batch.push_sprite(&Sprite::new(0, 0, Layer::Ui, "A"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(1, 0, Layer::Background, "A"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(2, 0, Layer::Actors, "A"), 0.0, 0.0);
+
+        assert_eq!(sheets_and_cells(&mut batch), vec![
+            vec![("A".to_string(), 1)],
+            vec![("A".to_string(), 2)],
+            vec![("A".to_string(), 0)],
+        ]);
+    }
+
+    #[test]
+    fn should_keep_drawing_order_within_a_layer() {
+        let mut batch = SpriteBatch::new(1, 1);
+        for x in 0..4 {
+            batch.push_sprite(&Sprite::new(x, 0, Layer::Items, "A"), 0.0, 0.0);
+        }
+        batch.push_sprite(&Sprite::new(4, 0, Layer::Items, "A").with_depth(-1.0), 0.0, 0.0);
+
+        assert_eq!(sheets_and_cells(&mut batch), vec![
+            (0..5).map(|x| ("A".to_string(), (x + 4) % 5)).collect::<Vec<_>>(),
+        ]);
+    }
+
+    #[test]
+    fn should_group_neighbouring_sprites_from_the_same_sheet() {
+        let mut batch = SpriteBatch::new(1, 1);
+        batch.push_sprite(&Sprite::new(0, 0, Layer::Items, "B"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(1, 0, Layer::Items, "B"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(2, 0, Layer::Items, "B").with_palette("Red"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(3, 0, Layer::Items, "A"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(4, 0, Layer::Items, "B"), 0.0, 0.0);
+
+        assert_eq!(sheets_and_cells(&mut batch), vec![
+            vec![("B".to_string(), 0), ("B".to_string(), 1)],
+            vec![("B#Red".to_string(), 2)],
+            vec![("A".to_string(), 3)],
+            vec![("B".to_string(), 4)],
+        ]);
+    }
+
+    #[test]
+    fn should_not_reorder_sprites_of_the_same_depth_by_sheet() {
+        let mut batch = SpriteBatch::new(1, 1);
+        batch.push_sprite(&Sprite::new(0, 0, Layer::Actors, "Walls"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(1, 0, Layer::Actors, "Sprites"), 0.0, 0.0);
+        batch.push_sprite(&Sprite::new(2, 0, Layer::Actors, "Sprites").with_depth(-1.0), 0.0, 0.0);
+
+        assert_eq!(sheets_and_cells(&mut batch), vec![
+            vec![("Sprites".to_string(), 2)],
+            vec![("Walls".to_string(), 0)],
+            vec![("Sprites".to_string(), 1)],
+        ]);
+    }
+
+    #[test]
+    fn should_place_and_clip_to_the_viewport() {
+        let mut batch = SpriteBatch::new(2, 2);
+        batch.set_viewport(Some(&Viewport::new(5.0, 1.0, 5.0, 4.0)));
+        batch.set_camera(Some(&Camera::new(5.0, 4.0).shifted(10.0, 0.0)));
+        batch.push_sprite(&Sprite::new(0, 0, Layer::Items, "A"), 11.0, 2.0);
+        batch.set_viewport(None);
+        batch.set_camera(None);
+        batch.push_sprite(&Sprite::new(1, 0, Layer::Items, "A"), 11.0, 2.0);
+
+        let placed: Vec<((i32, i32), Option<Clip>)> = batch.runs().iter()
+            .flat_map(|run| run.iter().map(|(_, position, clip)| (*position, *clip)))
+            .collect();
+        assert_eq!(placed, vec![((12, 6), Some((10, 2, 10, 8))), ((22, 4), None)]);
+    }
+
+    #[test]
+    fn should_cut_lines_and_fills_at_the_edge_of_the_viewport() {
+        let mut batch = SpriteBatch::new(1, 1);
+        batch.set_viewport(Some(&Viewport::new(0.0, 0.0, 10.0, 10.0)));
+        batch.push_line((-10.0, 5.0), (20.0, 5.0), (255, 255, 255, 255));
+        batch.push_line((-10.0, 20.0), (20.0, 20.0), (255, 255, 255, 255));
+        batch.push_fill(5.0, 5.0, 10.0, 10.0, (255, 255, 255, 255));
+
+        assert_eq!(batch.lines(), &vec![((0, 5), (9, 5), (255, 255, 255, 255))]);
+        assert_eq!(batch.fills(), &vec![((5, 5, 5, 5), (255, 255, 255, 255))]);
+    }
+
+    #[test]
+    fn should_start_afresh_after_clearing() {
+        let mut batch = SpriteBatch::new(1, 1);
+        batch.push_sprite(&Sprite::new(0, 0, Layer::Items, "A"), 0.0, 0.0);
+        batch.push_line((0.0, 0.0), (1.0, 1.0), (255, 255, 255, 255));
+        batch.clear();
+
+        assert!(batch.runs().is_empty());
+        assert!(batch.lines().is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub zoom: f64,
+    dead_zone: (f64, f64),
+    bounds: Option<(f64, f64, f64, f64)>,
+}
+
+impl Camera {
+    pub fn new(width: f64, height: f64) -> Self {
+        Camera { x: 0.0, y: 0.0, width, height, zoom: 1.0, dead_zone: (0.0, 0.0), bounds: None }
+    }
+
+    pub fn with_dead_zone(mut self, width: f64, height: f64) -> Self {
+        self.dead_zone = (width, height);
+        self
+    }
+
+    pub fn with_bounds(mut self, left: f64, bottom: f64, right: f64, top: f64) -> Self {
+        self.bounds = Some((left, bottom, right, top));
+        self.clamp();
+        self
+    }
+
+    // zooming scales about the center of the view, leaving its position and size as they were
+    pub fn with_zoom(self, zoom: f64) -> Self {
+        Camera { zoom, ..self }
+    }
+
+    // keeps looking at the same place, as far as the bounds allow
+    pub fn resized(mut self, width: f64, height: f64) -> Self {
+        let (center_x, center_y) = self.center();
+        self.width = width;
+        self.height = height;
+        self.center_on(center_x, center_y);
+        self
+    }
+
+    pub fn shifted(self, dx: f64, dy: f64) -> Self {
+        Camera { x: self.x + dx, y: self.y + dy, ..self }
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    pub fn center_on(&mut self, x: f64, y: f64) {
+        self.x = x - self.width / 2.0;
+        self.y = y - self.height / 2.0;
+        self.clamp();
+    }
+
+    pub fn follow(&mut self, x: f64, y: f64) {
+        let (center_x, center_y) = self.center();
+        let (dead_width, dead_height) = self.dead_zone;
+        self.x += outside(x - center_x, dead_width / 2.0);
+        self.y += outside(y - center_y, dead_height / 2.0);
+        self.clamp();
+    }
+
+    pub fn to_view(&self, x: f64, y: f64) -> (f64, f64) {
+        let (center_x, center_y) = self.center();
+        ((x - center_x) * self.zoom + self.width / 2.0, (y - center_y) * self.zoom + self.height / 2.0)
+    }
+
+    pub fn can_see(&self, x: f64, y: f64, width: f64, height: f64) -> bool {
+        let (center_x, center_y) = self.center();
+        let (half_width, half_height) = (self.width / (2.0 * self.zoom), self.height / (2.0 * self.zoom));
+        x + width > center_x - half_width && x < center_x + half_width && y + height > center_y - half_height && y < center_y + half_height
+    }
+
+    fn clamp(&mut self) {
+        if let Some((left, bottom, right, top)) = self.bounds {
+            self.x = clamp_axis(self.x, left, right, self.width);
+            self.y = clamp_axis(self.y, bottom, top, self.height);
+        }
+    }
+}
+
+fn outside(offset: f64, half_zone: f64) -> f64 {
+    if offset > half_zone {
+        offset - half_zone
+    } else if offset < -half_zone {
+        offset + half_zone
+    } else {
+        0.0
+    }
+}
+
+fn clamp_axis(position: f64, min: f64, max: f64, view: f64) -> f64 {
+    if max - min <= view {
+        // the map is smaller than the view, so keep it centered
+        min - (view - (max - min)) / 2.0
+    } else {
+        position.clamp(min, max - view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_not_move_while_target_is_inside_dead_zone() {
+        let mut camera = Camera::new(10.0, 10.0).with_dead_zone(4.0, 2.0);
+        camera.follow(6.5, 5.5);
+
+        assert_eq!((camera.x, camera.y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn should_drag_camera_when_target_leaves_dead_zone() {
+        let mut camera = Camera::new(10.0, 10.0).with_dead_zone(4.0, 2.0);
+        camera.follow(10.0, 2.0);
+
+        assert_eq!((camera.x, camera.y), (3.0, -2.0));
+    }
+
+    #[test]
+    fn should_clamp_to_map_bounds() {
+        let mut camera = Camera::new(10.0, 10.0).with_bounds(0.0, 0.0, 40.0, 20.0);
+        camera.follow(100.0, -100.0);
+        assert_eq!((camera.x, camera.y), (30.0, 0.0));
+
+        camera.center_on(20.0, 12.0);
+        assert_eq!((camera.x, camera.y), (15.0, 7.0));
+    }
+
+    #[test]
+    fn should_center_maps_smaller_than_the_view() {
+        let mut camera = Camera::new(10.0, 10.0).with_bounds(0.0, 0.0, 6.0, 10.0);
+        camera.follow(50.0, 50.0);
+
+        assert_eq!((camera.x, camera.y), (-2.0, 0.0));
+    }
+
+    #[test]
+    fn should_transform_world_to_view_coordinates() {
+        let mut camera = Camera::new(10.0, 10.0).with_bounds(0.0, 0.0, 40.0, 40.0);
+        camera.center_on(20.0, 20.0);
+
+        assert_eq!(camera.to_view(16.0, 17.5), (1.0, 2.5));
+        assert!(camera.can_see(14.5, 14.5, 1.0, 1.0));
+        assert!(!camera.can_see(13.0, 20.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn should_zoom_about_the_center_of_the_view() {
+        let mut camera = Camera::new(10.0, 10.0).with_bounds(0.0, 0.0, 40.0, 40.0);
+        camera.center_on(20.0, 20.0);
+        let zoomed = camera.clone().with_zoom(2.0);
+
+        assert_eq!(zoomed.to_view(20.0, 20.0), (5.0, 5.0));
+        assert_eq!(zoomed.to_view(22.0, 19.0), (9.0, 3.0));
+        assert!(camera.can_see(15.5, 20.0, 1.0, 1.0));
+        assert!(!zoomed.can_see(15.5, 20.0, 1.0, 1.0));
+        assert_eq!((zoomed.x, zoomed.y, zoomed.width), (camera.x, camera.y, camera.width));
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Duration;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, Rgba, RgbaImage};
+
+use component_derive::Event;
+
+use crate::error::EngineError;
+use crate::events::EventTrait;
+use crate as engine;
+
+#[derive(Event)]
+pub struct TakeScreenshot(pub String);
+
+#[derive(Event)]
+pub struct SaveRecording(pub String);
+
+pub trait Captured {
+    fn screenshot(&mut self) -> Result<RgbaImage, EngineError>;
+
+    fn recorder(&mut self) -> &mut FrameRecorder;
+}
+
+pub struct FrameRecorder {
+    frames: VecDeque<RgbaImage>,
+    capacity: usize,
+    interval: Duration,
+    since_last: Duration,
+    enabled: bool,
+}
+
+impl FrameRecorder {
+    pub fn new(length: Duration, interval: Duration) -> Self {
+        let capacity = (length.as_secs_f64() / interval.as_secs_f64()).ceil().max(1.0) as usize;
+        FrameRecorder { frames: VecDeque::with_capacity(capacity), capacity, interval, since_last: interval, enabled: true }
+    }
+
+    // keeping frames means reading each one back, which the SDL backend pays for on every recorded frame
+    pub fn with_enabled(self, enabled: bool) -> Self {
+        FrameRecorder { enabled, ..self }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    // true when enough time has passed that the next frame should be recorded
+    pub fn tick(&mut self, elapsed: Duration) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        self.since_last += elapsed;
+        if self.since_last >= self.interval {
+            self.since_last -= self.interval;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn push(&mut self, frame: RgbaImage) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    pub fn frames(&self) -> Vec<RgbaImage> {
+        self.frames.iter().cloned().collect()
+    }
+}
+
+// the surface is transparent where nothing was drawn; captures show it over black, as on screen
+pub fn flatten(frame: &RgbaImage) -> RgbaImage {
+    let mut flattened = frame.clone();
+    for Rgba([r, g, b, a]) in flattened.pixels_mut() {
+        let scale = |c: u8| ((c as u32 * *a as u32 + 127) / 255) as u8;
+        *r = scale(*r);
+        *g = scale(*g);
+        *b = scale(*b);
+        *a = 255;
+    }
+    flattened
+}
+
+pub fn save_png(frame: &RgbaImage, path: &str) -> Result<(), EngineError> {
+    flatten(frame).save(path).map_err(|err| EngineError::Asset(err.to_string()))
+}
+
+pub fn save_gif(frames: &[RgbaImage], delay: Duration, path: &str) -> Result<(), EngineError> {
+    let file = File::create(path).map_err(|err| EngineError::Asset(err.to_string()))?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+    encoder.set_repeat(Repeat::Infinite).map_err(|err| EngineError::Asset(err.to_string()))?;
+    let delay = Delay::from_numer_denom_ms(delay.as_millis() as u32, 1);
+    encoder.encode_frames(frames.iter().map(|frame| Frame::from_parts(flatten(frame), 0, 0, delay)))
+        .map_err(|err| EngineError::Asset(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifDecoder;
+    use image::AnimationDecoder;
+
+    fn frame(shade: u8) -> RgbaImage {
+        RgbaImage::from_pixel(4, 2, Rgba([shade, shade, shade, 255]))
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(name).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn should_record_at_the_given_interval() {
+        let mut recorder = FrameRecorder::new(Duration::from_secs(1), Duration::from_millis(100));
+
+        assert!(recorder.tick(Duration::from_millis(16)));
+        assert!(!recorder.tick(Duration::from_millis(60)));
+        assert!(recorder.tick(Duration::from_millis(60)));
+    }
+
+    #[test]
+    fn should_carry_time_over_so_recordings_keep_pace() {
+        let mut recorder = FrameRecorder::new(Duration::from_secs(1), Duration::from_millis(100));
+        let recorded = (0..60).filter(|_| recorder.tick(Duration::from_millis(30))).count();
+
+        assert_eq!(recorded, 19);
+    }
+
+    #[test]
+    fn should_record_nothing_when_disabled() {
+        let mut recorder = FrameRecorder::new(Duration::from_secs(1), Duration::from_millis(100)).with_enabled(false);
+
+        assert!(!recorder.tick(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn should_keep_only_the_most_recent_frames() {
+        let mut recorder = FrameRecorder::new(Duration::from_millis(300), Duration::from_millis(100));
+        for shade in 0..5 {
+            recorder.push(frame(shade));
+        }
+
+        let shades: Vec<u8> = recorder.frames().iter().map(|frame| frame.get_pixel(0, 0)[0]).collect();
+        assert_eq!(shades, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn should_flatten_onto_black() {
+        let image = RgbaImage::from_pixel(1, 1, Rgba([200, 100, 0, 128]));
+
+        assert_eq!(flatten(&image).get_pixel(0, 0), &Rgba([100, 50, 0, 255]));
+    }
+
+    #[test]
+    fn should_save_screenshots_and_recordings() {
+        let png = temp_path("engine-capture-test.png");
+        save_png(&frame(10), &png).unwrap();
+        assert_eq!(image::open(&png).unwrap().to_rgba8(), frame(10));
+
+        let gif = temp_path("engine-capture-test.gif");
+        save_gif(&[frame(0), frame(255)], Duration::from_millis(100), &gif).unwrap();
+        let decoded = GifDecoder::new(File::open(&gif).unwrap()).unwrap().into_frames().collect_frames().unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[1].buffer().get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+    }
+}
@@ -0,0 +1,223 @@
+use sdl2::hint;
+use sdl2::video::Window;
+use sdl2::VideoSubsystem;
+
+use component_derive::Event;
+
+use crate::error::EngineError;
+use crate::events::EventTrait;
+use crate as engine;
+
+use super::postprocess::PostFilter;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    Fullscreen,
+    FullscreenDesktop,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleFilter {
+    Nearest,
+    Linear,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayConfig {
+    pub mode: WindowMode,
+    pub window_size: (u32, u32),
+    pub integer_scaling: bool,
+    pub filter: ScaleFilter,
+    pub post_filters: Vec<PostFilter>,
+    pub recording: bool,
+}
+
+// the mouse, in tiles up from the bottom left of the picture
+#[derive(Event)]
+pub struct PointerMoved(pub f64, pub f64);
+
+#[derive(Event)]
+pub struct PointerPressed(pub f64, pub f64);
+
+pub trait Displayed {
+    fn window_resized(&mut self) -> Result<(), EngineError>;
+
+    fn window_to_view(&self, x: i32, y: i32) -> Option<(f64, f64)>;
+}
+
+impl DisplayConfig {
+    pub fn new(window_width: u32, window_height: u32) -> Self {
+        DisplayConfig {
+            mode: WindowMode::FullscreenDesktop,
+            window_size: (window_width, window_height),
+            integer_scaling: false,
+            filter: ScaleFilter::Nearest,
+            post_filters: Vec::new(),
+            recording: false,
+        }
+    }
+
+    pub fn with_mode(self, mode: WindowMode) -> Self {
+        DisplayConfig { mode, ..self }
+    }
+
+    pub fn with_integer_scaling(self, integer_scaling: bool) -> Self {
+        DisplayConfig { integer_scaling, ..self }
+    }
+
+    pub fn with_filter(self, filter: ScaleFilter) -> Self {
+        DisplayConfig { filter, ..self }
+    }
+
+    pub fn with_post_filter(mut self, filter: PostFilter) -> Self {
+        if !self.post_filters.contains(&filter) {
+            self.post_filters.push(filter);
+        }
+        self
+    }
+
+    pub fn with_recording(self, recording: bool) -> Self {
+        DisplayConfig { recording, ..self }
+    }
+
+    // unrecognised arguments are left for whoever else wants them
+    pub fn with_args<I: IntoIterator<Item = String>>(self, args: I) -> Self {
+        args.into_iter().fold(self, |config, arg| match arg.as_str() {
+            "--windowed" => config.with_mode(WindowMode::Windowed),
+            "--fullscreen" => config.with_mode(WindowMode::Fullscreen),
+            "--fullscreen-desktop" => config.with_mode(WindowMode::FullscreenDesktop),
+            "--integer-scaling" => config.with_integer_scaling(true),
+            "--smooth" => config.with_filter(ScaleFilter::Linear),
+            "--record" => config.with_recording(true),
+            "--crt" => PostFilter::ALL.iter().fold(config, |config, filter| config.with_post_filter(*filter)),
+            _ => match (arg.strip_prefix("--window-size=").and_then(parse_size), arg.strip_prefix("--").and_then(PostFilter::from_name)) {
+                (Some(window_size), _) => DisplayConfig { window_size, ..config },
+                (_, Some(filter)) => config.with_post_filter(filter),
+                _ => config,
+            },
+        })
+    }
+
+    // the filter only applies to textures created afterwards, so this has to come before the renderer
+    pub fn open_window(&self, video_subsystem: &VideoSubsystem, title: &str) -> Result<Window, EngineError> {
+        hint::set("SDL_RENDER_SCALE_QUALITY", match self.filter {
+            ScaleFilter::Nearest => "nearest",
+            ScaleFilter::Linear => "linear",
+        });
+        let (width, height) = self.window_size;
+        let mut builder = video_subsystem.window(title, width, height);
+        match self.mode {
+            WindowMode::Windowed => builder.position_centered().resizable(),
+            WindowMode::Fullscreen => builder.fullscreen(),
+            WindowMode::FullscreenDesktop => builder.fullscreen_desktop(),
+        };
+        builder.build().map_err(|err| EngineError::Render(err.to_string()))
+    }
+}
+
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+// letterboxes the picture in the middle of the output; with integer scaling it's only ever drawn at whole
+// multiples of its size, unless the output is too small for even one
+pub fn target_rect((output_width, output_height): (u32, u32), (width, height): (u32, u32), integer_scaling: bool) -> (i32, i32, u32, u32) {
+    let scale = f64::min(output_width as f64 / width as f64, output_height as f64 / height as f64);
+    let scale = if integer_scaling && scale >= 1.0 { scale.floor() } else { scale };
+
+    let scaled_width = (width as f64 * scale) as u32;
+    let scaled_height = (height as f64 * scale) as u32;
+    let x_offset = (output_width - scaled_width) / 2;
+    let y_offset = (output_height - scaled_height) / 2;
+    (x_offset as i32, y_offset as i32, scaled_width, scaled_height)
+}
+
+// undoes target_rect for a point on the output; nothing comes back for points in the letterbox
+pub fn view_position(
+    (left, top, width, height): (i32, i32, u32, u32),
+    (view_width, view_height): (u32, u32),
+    (tile_width, tile_height): (u32, u32),
+    (x, y): (i32, i32)
+) -> Option<(f64, f64)> {
+    // a minimised window has nothing to point at
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let px = (x - left) as f64 * view_width as f64 / width as f64;
+    let py = (y - top) as f64 * view_height as f64 / height as f64;
+    if px < 0.0 || py < 0.0 || px >= view_width as f64 || py >= view_height as f64 {
+        return None;
+    }
+    Some((px / tile_width as f64, (view_height as f64 - py) / tile_height as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn should_fill_the_output_by_default() {
+        assert_eq!(target_rect((1920, 1080), (360, 240), false), (150, 0, 1620, 1080));
+    }
+
+    #[test]
+    fn should_letterbox_to_whole_multiples() {
+        assert_eq!(target_rect((1920, 1080), (360, 240), true), (240, 60, 1440, 960));
+        assert_eq!(target_rect((1080, 720), (360, 240), true), (0, 0, 1080, 720));
+    }
+
+    #[test]
+    fn should_shrink_when_too_small_for_integer_scaling() {
+        assert_eq!(target_rect((180, 240), (360, 240), true), (0, 60, 180, 120));
+    }
+
+    #[test]
+    fn should_find_points_in_the_picture() {
+        let rect = target_rect((1920, 1080), (360, 240), true);
+
+        assert_eq!(view_position(rect, (360, 240), (12, 12), (288, 1008)), Some((1.0, 0.25)));
+        assert_eq!(view_position(rect, (360, 240), (12, 12), (960, 540)), Some((15.0, 10.0)));
+        assert_eq!(view_position(rect, (360, 240), (12, 12), (100, 540)), None);
+    }
+
+    #[test]
+    fn should_find_nothing_in_an_empty_picture() {
+        assert_eq!(view_position((0, 0, 0, 240), (360, 240), (12, 12), (0, 0)), None);
+        assert_eq!(view_position((0, 0, 360, 0), (360, 240), (12, 12), (0, 0)), None);
+    }
+
+    #[test]
+    fn should_read_options_from_arguments() {
+        let config = DisplayConfig::new(1080, 720)
+            .with_args(args(&["--windowed", "--integer-scaling", "--smooth", "--window-size=720x480", "--scanlines", "--record", "--unknown"]));
+
+        assert_eq!(config, DisplayConfig {
+            mode: WindowMode::Windowed,
+            window_size: (720, 480),
+            integer_scaling: true,
+            filter: ScaleFilter::Linear,
+            post_filters: vec![PostFilter::Scanlines],
+            recording: true,
+        });
+    }
+
+    #[test]
+    fn should_choose_every_filter_for_a_crt_look() {
+        let config = DisplayConfig::new(1080, 720).with_args(args(&["--bloom", "--crt"]));
+
+        assert_eq!(config.post_filters.len(), PostFilter::ALL.len());
+    }
+
+    #[test]
+    fn should_ignore_malformed_window_sizes() {
+        let config = DisplayConfig::new(1080, 720).with_args(args(&["--window-size=big"]));
+
+        assert_eq!(config, DisplayConfig::new(1080, 720));
+    }
+}
@@ -0,0 +1,202 @@
+use std::time::Duration;
+
+use component_derive::Event;
+
+use crate::events::{Event, EventTrait};
+use crate as engine;
+
+use super::camera::Camera;
+
+#[derive(Event)]
+pub struct CameraShake(pub f64, pub Duration);
+
+#[derive(Event)]
+pub struct CameraFlash(pub (u8, u8, u8), pub Duration);
+
+#[derive(Event)]
+pub struct CameraZoom(pub f64, pub Duration);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Fading<T> {
+    value: T,
+    elapsed: f64,
+    duration: f64,
+}
+
+impl<T> Fading<T> {
+    fn new(value: T, duration: &Duration) -> Self {
+        Fading { value, elapsed: 0.0, duration: duration.as_secs_f64() }
+    }
+
+    // goes from 1 down to 0 over the duration
+    fn remaining(&self) -> f64 {
+        if self.duration <= 0.0 {
+            0.0
+        } else {
+            (1.0 - self.elapsed / self.duration).max(0.0)
+        }
+    }
+}
+
+// shake comes from trauma, which adds up and wears off; the picture moves with the square of it,
+// so small knocks barely register and big ones are violent
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraEffects {
+    max_offset: f64,
+    trauma: f64,
+    recovery: f64,
+    time: f64,
+    flash: Option<Fading<(u8, u8, u8)>>,
+    zoom: Option<Fading<f64>>,
+}
+
+impl CameraEffects {
+    pub fn new(max_offset: f64) -> Self {
+        CameraEffects { max_offset, trauma: 0.0, recovery: 0.0, time: 0.0, flash: None, zoom: None }
+    }
+
+    pub fn shake(&mut self, intensity: f64, duration: &Duration) {
+        self.trauma = (self.trauma + intensity).clamp(0.0, 1.0);
+        self.recovery = self.recovery.max(self.trauma / duration.as_secs_f64().max(f64::EPSILON));
+    }
+
+    pub fn flash(&mut self, colour: (u8, u8, u8), duration: &Duration) {
+        self.flash = Some(Fading::new(colour, duration));
+    }
+
+    // a punch jumps in by the amount, then eases back out
+    pub fn zoom(&mut self, amount: f64, duration: &Duration) {
+        self.zoom = Some(Fading::new(amount, duration));
+    }
+
+    pub fn event(&mut self, event: &Event) {
+        event.apply(|CameraShake(intensity, duration)| self.shake(*intensity, duration));
+        event.apply(|CameraFlash(colour, duration)| self.flash(*colour, duration));
+        event.apply(|CameraZoom(amount, duration)| self.zoom(*amount, duration));
+    }
+
+    pub fn update(&mut self, dt: &Duration) {
+        let dt = dt.as_secs_f64();
+        self.time += dt;
+        self.trauma = (self.trauma - self.recovery * dt).max(0.0);
+        if self.trauma == 0.0 {
+            self.recovery = 0.0;
+        }
+        if let Some(flash) = self.flash.as_mut() {
+            flash.elapsed += dt;
+        }
+        if let Some(zoom) = self.zoom.as_mut() {
+            zoom.elapsed += dt;
+        }
+        self.flash = self.flash.take().filter(|flash| flash.remaining() > 0.0);
+        self.zoom = self.zoom.take().filter(|zoom| zoom.remaining() > 0.0);
+    }
+
+    pub fn trauma(&self) -> f64 {
+        self.trauma
+    }
+
+    // a few sines at unrelated frequencies wander about without repeating noticeably
+    pub fn offset(&self) -> (f64, f64) {
+        let shake = self.trauma * self.trauma * self.max_offset;
+        let wobble = |phase: f64| ((self.time * 37.0 + phase).sin() + (self.time * 61.0 + phase * 2.0).sin()) / 2.0;
+        (shake * wobble(0.0), shake * wobble(1.7))
+    }
+
+    pub fn zoom_factor(&self) -> f64 {
+        self.zoom.as_ref().map_or(1.0, |zoom| 1.0 + zoom.value * zoom.remaining() * zoom.remaining())
+    }
+
+    pub fn flash_colour(&self) -> Option<(u8, u8, u8, u8)> {
+        self.flash.as_ref().map(|flash| {
+            let (r, g, b) = flash.value;
+            (r, g, b, (255.0 * flash.remaining()).round() as u8)
+        })
+    }
+
+    // the camera to draw with; the one passed in keeps following as though nothing happened
+    pub fn apply(&self, camera: &Camera) -> Camera {
+        let (dx, dy) = self.offset();
+        camera.clone().shifted(dx, dy).with_zoom(camera.zoom * self.zoom_factor())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(effects: &mut CameraEffects, millis: u64) {
+        effects.update(&Duration::from_millis(millis));
+    }
+
+    #[test]
+    fn should_not_move_the_camera_when_calm() {
+        let mut effects = CameraEffects::new(0.5);
+        step(&mut effects, 100);
+
+        assert_eq!(effects.apply(&Camera::new(10.0, 10.0)), Camera::new(10.0, 10.0));
+        assert_eq!(effects.flash_colour(), None);
+    }
+
+    #[test]
+    fn should_wear_off_trauma_over_the_duration() {
+        let mut effects = CameraEffects::new(0.5);
+        effects.shake(0.8, &Duration::from_millis(400));
+        step(&mut effects, 200);
+        assert!((effects.trauma() - 0.4).abs() < 1e-9);
+
+        step(&mut effects, 300);
+        assert_eq!(effects.trauma(), 0.0);
+        assert_eq!(effects.offset(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn should_add_up_trauma_to_a_limit() {
+        let mut effects = CameraEffects::new(0.5);
+        effects.shake(0.7, &Duration::from_secs(1));
+        effects.shake(0.7, &Duration::from_secs(1));
+
+        assert_eq!(effects.trauma(), 1.0);
+    }
+
+    #[test]
+    fn should_shake_within_the_maximum_offset() {
+        let mut effects = CameraEffects::new(0.5);
+        effects.shake(1.0, &Duration::from_secs(10));
+        let mut moved = false;
+        for _ in 0..20 {
+            step(&mut effects, 16);
+            let (dx, dy) = effects.offset();
+            assert!(dx.abs() <= 0.5 && dy.abs() <= 0.5);
+            moved |= dx != 0.0 || dy != 0.0;
+        }
+        assert!(moved);
+    }
+
+    #[test]
+    fn should_fade_the_flash_out() {
+        let mut effects = CameraEffects::new(0.5);
+        effects.event(&Event::new(CameraFlash((255, 0, 0), Duration::from_millis(400))));
+        assert_eq!(effects.flash_colour(), Some((255, 0, 0, 255)));
+
+        step(&mut effects, 100);
+        assert_eq!(effects.flash_colour(), Some((255, 0, 0, 191)));
+
+        step(&mut effects, 300);
+        assert_eq!(effects.flash_colour(), None);
+    }
+
+    #[test]
+    fn should_punch_in_and_ease_back_out() {
+        let mut effects = CameraEffects::new(0.5);
+        effects.zoom(0.2, &Duration::from_millis(200));
+        assert!((effects.zoom_factor() - 1.2).abs() < 1e-9);
+
+        step(&mut effects, 100);
+        assert!((effects.zoom_factor() - 1.05).abs() < 1e-9);
+        assert!((effects.apply(&Camera::new(10.0, 10.0)).zoom - 1.05).abs() < 1e-9);
+
+        step(&mut effects, 100);
+        assert_eq!(effects.zoom_factor(), 1.0);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+pub const DEFAULT_FONT: &str = "Text";
+
+pub const STANDARD_CHARACTERS: [&str; 7] = [
+    "0123456789",
+    "abcdefghij",
+    "klmnopqrst",
+    "uvwxyz:-?!",
+    "ABCDEFGHIJ",
+    "KLMNOPQRST",
+    "UVWXYZ., ",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Font {
+    pub sheet: String,
+    pub glyph_width: u32,
+    pub glyph_height: u32,
+    pub spacing: i32,
+    pub line_spacing: i32,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), i32>,
+    fallback: Glyph,
+}
+
+impl Font {
+    pub fn new(sheet: &str, glyph_width: u32, glyph_height: u32) -> Self {
+        Font {
+            sheet: sheet.to_string(),
+            glyph_width,
+            glyph_height,
+            spacing: 0,
+            line_spacing: 0,
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+            fallback: Glyph { x: 0, y: 0, width: glyph_width },
+        }
+    }
+
+    pub fn standard(sheet: &str, glyph_width: u32, glyph_height: u32) -> Self {
+        Font::new(sheet, glyph_width, glyph_height)
+            .with_char_map(&STANDARD_CHARACTERS)
+            .with_fallback(9, 6)
+    }
+
+    // each row of the map lists the characters along that row of the sheet
+    pub fn with_char_map(mut self, rows: &[&str]) -> Self {
+        for (y, row) in rows.iter().enumerate() {
+            for (x, ch) in row.chars().enumerate() {
+                self.glyphs.insert(ch, Glyph { x: x as i32, y: y as i32, width: self.glyph_width });
+            }
+        }
+        self
+    }
+
+    pub fn with_glyph(mut self, ch: char, x: i32, y: i32) -> Self {
+        self.glyphs.insert(ch, Glyph { x, y, width: self.glyph_width });
+        self
+    }
+
+    pub fn with_width(mut self, chars: &str, width: u32) -> Self {
+        for ch in chars.chars() {
+            if let Some(glyph) = self.glyphs.get_mut(&ch) {
+                glyph.width = width;
+            }
+        }
+        self
+    }
+
+    pub fn with_kerning(mut self, left: char, right: char, adjustment: i32) -> Self {
+        self.kerning.insert((left, right), adjustment);
+        self
+    }
+
+    pub fn with_spacing(mut self, spacing: i32, line_spacing: i32) -> Self {
+        self.spacing = spacing;
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    pub fn with_fallback(mut self, x: i32, y: i32) -> Self {
+        self.fallback = Glyph { x, y, width: self.glyph_width };
+        self
+    }
+
+    pub fn glyph(&self, ch: char) -> Glyph {
+        *self.glyphs.get(&ch).unwrap_or(&self.fallback)
+    }
+
+    pub fn advance(&self, ch: char, next: Option<char>) -> i32 {
+        let kerning = next.and_then(|next| self.kerning.get(&(ch, next))).unwrap_or(&0);
+        self.glyph(ch).width as i32 + self.spacing + kerning
+    }
+
+    pub fn line_height(&self) -> i32 {
+        self.glyph_height as i32 + self.line_spacing
+    }
+
+    pub fn measure(&self, line: &[(usize, char)]) -> i32 {
+        if line.is_empty() {
+            return 0;
+        }
+        let advances: i32 = line.iter().enumerate()
+            .map(|(i, (_, ch))| self.advance(*ch, line.get(i + 1).map(|(_, next)| *next)))
+            .sum();
+        advances - self.spacing
+    }
+
+    // splits text into lines on newlines and, given a width in pixels, between words;
+    // characters keep their index into the original text so colour runs still line up
+    pub fn lines(&self, text: &str, max_width: Option<i32>) -> Vec<Vec<(usize, char)>> {
+        let mut lines = Vec::new();
+        let mut paragraph = Vec::new();
+        for (index, ch) in text.chars().enumerate() {
+            if ch == '\n' {
+                lines.append(&mut self.wrap(&paragraph, max_width));
+                paragraph.clear();
+            } else {
+                paragraph.push((index, ch));
+            }
+        }
+        lines.append(&mut self.wrap(&paragraph, max_width));
+        lines
+    }
+
+    fn wrap(&self, paragraph: &[(usize, char)], max_width: Option<i32>) -> Vec<Vec<(usize, char)>> {
+        let max_width = match max_width {
+            Some(max_width) => max_width,
+            None => return vec![paragraph.to_vec()],
+        };
+        let mut lines = Vec::new();
+        let mut line: Vec<(usize, char)> = Vec::new();
+        for word in paragraph.split(|(_, ch)| *ch == ' ') {
+            let mut candidate = line.clone();
+            if let Some((last, _)) = line.last() {
+                candidate.push((last + 1, ' '));
+            }
+            candidate.extend_from_slice(word);
+            if !line.is_empty() && self.measure(&candidate) > max_width {
+                lines.push(line);
+                line = word.to_vec();
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font() -> Font {
+        Font::standard("Text", 8, 8)
+            .with_width("i", 4)
+            .with_kerning('A', 'V', -2)
+    }
+
+    fn text_of(line: &[(usize, char)]) -> String {
+        line.iter().map(|(_, ch)| ch).collect()
+    }
+
+    #[test]
+    fn should_map_characters_to_tiles() {
+        let font = font();
+
+        assert_eq!(font.glyph('0'), Glyph { x: 0, y: 0, width: 8 });
+        assert_eq!(font.glyph('c'), Glyph { x: 2, y: 1, width: 8 });
+        assert_eq!(font.glyph('Z'), Glyph { x: 5, y: 6, width: 8 });
+        assert_eq!(font.glyph('#'), Glyph { x: 9, y: 6, width: 8 });
+    }
+
+    #[test]
+    fn should_measure_variable_widths_and_kerning() {
+        let font = font().with_spacing(1, 0);
+        let chars: Vec<(usize, char)> = "AVi".chars().enumerate().collect();
+
+        assert_eq!(font.measure(&chars), 8 + 1 - 2 + 8 + 1 + 4);
+    }
+
+    #[test]
+    fn should_split_lines_on_newlines() {
+        let lines = font().lines("ab\ncd", None);
+
+        assert_eq!(lines.iter().map(|line| text_of(line)).collect::<Vec<_>>(), vec!["ab", "cd"]);
+        assert_eq!(lines[1][0], (3, 'c'));
+    }
+
+    #[test]
+    fn should_wrap_between_words() {
+        let lines = font().lines("one two three four", Some(8 * 8));
+
+        assert_eq!(lines.iter().map(|line| text_of(line)).collect::<Vec<_>>(), vec!["one two", "three", "four"]);
+        assert_eq!(lines[2][0], (14, 'f'));
+    }
+
+    #[test]
+    fn should_not_break_words_longer_than_the_line() {
+        let lines = font().lines("a enormous b", Some(3 * 8));
+
+        assert_eq!(lines.iter().map(|line| text_of(line)).collect::<Vec<_>>(), vec!["a", "enormous", "b"]);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::EngineError;
+
+use super::animation::Animation;
+use super::layer::Layer;
+use super::sprite::Sprite;
+
+// a named cell (or block of cells) on a sheet, counted across and down from the top left
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub sheet: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Frame {
+    pub fn sprite(&self, layer: Layer) -> Sprite {
+        Sprite::multi(self.x, self.y, layer, self.width, self.height, &self.sheet)
+    }
+}
+
+// names for the frames of the sprite sheets and the animations made from them, so sprites can be asked for
+// as "panda/run" rather than by where they happen to sit on the sheet. The metadata is plain text, one entry a line:
+//
+//   sheet Sprites                          the sheet the frames after it are on
+//   frame panda/stand 0 2                  a cell, across then down
+//   frame hud/banner 8 6 3 1               a block of cells, with its width and height
+//   animation panda/run panda/run/0:100 panda/run/1:100
+//                                          frames already named, each shown for so many milliseconds
+//
+// and anything after a # is ignored
+#[derive(Clone, Debug, Default)]
+pub struct SpriteFrames {
+    frames: HashMap<String, Frame>,
+    animations: HashMap<String, Vec<(String, Duration)>>,
+}
+
+impl SpriteFrames {
+    pub fn new() -> Self {
+        SpriteFrames::default()
+    }
+
+    pub fn parse(source: &str, text: &str) -> Result<Self, EngineError> {
+        let mut frames = SpriteFrames::new();
+        frames.load(source, text)?;
+        Ok(frames)
+    }
+
+    // adds to what's already been loaded; the source only names the file in errors
+    pub fn load(&mut self, source: &str, text: &str) -> Result<(), EngineError> {
+        let mut sheet: Option<String> = None;
+        for (number, line) in text.lines().enumerate() {
+            let error = |message: &str| EngineError::Asset(format!("{source} line {}: {message}", number + 1));
+            let words: Vec<&str> = line.split('#').next().unwrap_or("").split_whitespace().collect();
+            match words.as_slice() {
+                [] => {}
+                ["sheet", name] => sheet = Some(name.to_string()),
+                ["frame", name, cells @ ..] => {
+                    let sheet = sheet.clone().ok_or_else(|| error("frame before any sheet"))?;
+                    let numbers = cells.iter().map(|cell| cell.parse::<u32>()).collect::<Result<Vec<u32>, _>>()
+                        .map_err(|_| error("frame cells should be whole numbers"))?;
+                    let (x, y, width, height) = match numbers.as_slice() {
+                        [x, y] => (*x, *y, 1, 1),
+                        [x, y, width, height] => (*x, *y, *width, *height),
+                        _ => return Err(error("a frame needs a cell, and optionally a width and height")),
+                    };
+                    self.add_frame(name, Frame { sheet, x: x as i32, y: y as i32, width, height });
+                }
+                ["animation", name, steps @ ..] if !steps.is_empty() => {
+                    let steps = steps.iter().map(|step| {
+                        let (frame, millis) = step.split_once(':').ok_or_else(|| error("animation frames are written frame:milliseconds"))?;
+                        if !self.frames.contains_key(frame) {
+                            return Err(error(&format!("no frame called {frame}")));
+                        }
+                        let millis = millis.parse::<u64>().map_err(|_| error("frame durations should be whole milliseconds"))?;
+                        Ok((frame.to_string(), Duration::from_millis(millis)))
+                    }).collect::<Result<Vec<(String, Duration)>, EngineError>>()?;
+                    self.add_animation(name, steps);
+                }
+                _ => return Err(error(&format!("can't make sense of '{}'", line.trim()))),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_frame(&mut self, name: &str, frame: Frame) {
+        self.frames.insert(name.to_string(), frame);
+    }
+
+    // every step should name a frame that's already been added
+    pub fn add_animation(&mut self, name: &str, steps: Vec<(String, Duration)>) {
+        self.animations.insert(name.to_string(), steps);
+    }
+
+    pub fn frame(&self, name: &str) -> Result<&Frame, EngineError> {
+        self.frames.get(name).ok_or_else(|| EngineError::Asset(format!("no frame called {name}")))
+    }
+
+    pub fn sprite(&self, name: &str, layer: Layer) -> Result<Sprite, EngineError> {
+        self.frame(name).map(|frame| frame.sprite(layer))
+    }
+
+    pub fn animation(&self, name: &str, layer: Layer) -> Result<Animation, EngineError> {
+        let frames = self.steps(name)?.iter()
+            .map(|(frame, duration)| self.sprite(frame, layer).map(|sprite| (sprite, *duration)))
+            .collect::<Result<Vec<(Sprite, Duration)>, EngineError>>()?;
+        Ok(Animation::new(frames))
+    }
+
+    fn steps(&self, name: &str) -> Result<&Vec<(String, Duration)>, EngineError> {
+        self.animations.get(name).ok_or_else(|| EngineError::Asset(format!("no animation called {name}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = "
+        # the hero
+        sheet Sprites
+        frame panda/stand 0 2
+        frame panda/run/0 1 2   # legs out
+        frame panda/run/1 2 2
+        animation panda/run panda/run/0:100 panda/run/1:250 panda/run/0:100
+
+        sheet Walls
+        frame hud/banner 8 6 3 1
+    ";
+
+    #[test]
+    fn should_name_cells_on_the_sheet_before_them() {
+        let frames = SpriteFrames::parse("test.frames", METADATA).unwrap();
+
+        assert_eq!(frames.frame("panda/run/1").unwrap(), &Frame { sheet: "Sprites".to_string(), x: 2, y: 2, width: 1, height: 1 });
+        assert_eq!(frames.frame("hud/banner").unwrap(), &Frame { sheet: "Walls".to_string(), x: 8, y: 6, width: 3, height: 1 });
+    }
+
+    #[test]
+    fn should_make_sprites_from_frames() {
+        let sprite = SpriteFrames::parse("test.frames", METADATA).unwrap().sprite("hud/banner", Layer::Ui).unwrap();
+
+        assert_eq!((sprite.tileset.as_str(), sprite.x, sprite.y, sprite.width, sprite.height), ("Walls", 8, 6, 3, 1));
+        assert_eq!(sprite.layer, Layer::Ui);
+    }
+
+    #[test]
+    fn should_time_animation_frames() {
+        let frames = SpriteFrames::parse("test.frames", METADATA).unwrap();
+        let animation = frames.animation("panda/run", Layer::Actors).unwrap();
+
+        assert!((animation.length() - 0.45).abs() < 1e-9);
+        assert_eq!(animation.frame_at(0.05).map(|sprite| sprite.x), Some(1));
+        assert_eq!(animation.frame_at(0.2).map(|sprite| sprite.x), Some(2));
+        assert_eq!(animation.frame_at(0.4).map(|sprite| sprite.x), Some(1));
+    }
+
+    #[test]
+    fn should_add_to_frames_already_loaded() {
+        let mut frames = SpriteFrames::parse("test.frames", METADATA).unwrap();
+        frames.load("more.frames", "sheet Ui\nframe cursor 3 0\nanimation blink cursor:100 panda/stand:100").unwrap();
+
+        assert_eq!(frames.frame("cursor").unwrap().sheet, "Ui");
+        assert_eq!(frames.animation("blink", Layer::Ui).unwrap().frame_at(0.15).unwrap().tileset, "Sprites");
+    }
+
+    #[test]
+    fn should_report_where_metadata_goes_wrong() {
+        let error = |text: &str| SpriteFrames::parse("bad.frames", text).unwrap_err().to_string();
+
+        assert_eq!(error("frame x 0 0"), "asset error: bad.frames line 1: frame before any sheet");
+        assert_eq!(error("sheet A\nframe x 0"), "asset error: bad.frames line 2: a frame needs a cell, and optionally a width and height");
+        assert_eq!(error("sheet A\nanimation run x:100"), "asset error: bad.frames line 2: no frame called x");
+        assert_eq!(error("sheet A\nframe x 0 0\nanimation run x"), "asset error: bad.frames line 3: animation frames are written frame:milliseconds");
+        assert_eq!(error("colour red"), "asset error: bad.frames line 1: can't make sense of 'colour red'");
+    }
+
+    #[test]
+    fn should_complain_about_missing_names() {
+        let frames = SpriteFrames::parse("test.frames", METADATA).unwrap();
+
+        assert_eq!(frames.sprite("panda/jump", Layer::Actors).err().map(|err| err.to_string()), Some("asset error: no frame called panda/jump".to_string()));
+        assert!(frames.animation("panda/swim", Layer::Actors).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+use std::env;
+use std::path::PathBuf;
+
+use image::RgbaImage;
+
+// run with BLESS_GOLDEN=1 to accept the current output as the new golden image
+pub fn assert_matches_golden(frame: &RgbaImage, name: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("golden").join(format!("{name}.png"));
+    if env::var("BLESS_GOLDEN").is_ok() {
+        frame.save(&path).unwrap();
+        return;
+    }
+    let golden = image::open(&path)
+        .unwrap_or_else(|err| panic!("could not open golden image {}: {err}", path.display()))
+        .to_rgba8();
+    assert_eq!(golden.dimensions(), frame.dimensions(), "{name} has the wrong dimensions");
+    let mismatches = golden.pixels().zip(frame.pixels()).filter(|(expected, actual)| expected != actual).count();
+    assert_eq!(mismatches, 0, "{name} differs from its golden image in {mismatches} pixels");
+}
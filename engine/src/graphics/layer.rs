@@ -0,0 +1,23 @@
+// sprites are drawn a layer at a time, from the back; order within a layer comes from each sprite's depth
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    Background,
+    Tiles,
+    Items,
+    Actors,
+    Effects,
+    Ui,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 6] = [Layer::Background, Layer::Tiles, Layer::Items, Layer::Actors, Layer::Effects, Layer::Ui];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    // the interface goes on after the lighting, so it can always be read
+    pub fn is_lit(&self) -> bool {
+        *self != Layer::Ui
+    }
+}
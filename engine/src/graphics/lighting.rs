@@ -0,0 +1,111 @@
+use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+use component_derive::Variable;
+
+// a light that goes wherever the entity's position is; the radius is in tiles
+#[derive(Clone, Debug, PartialEq, Variable)]
+pub struct PointLight {
+    pub radius: f64,
+    pub colour: (u8, u8, u8),
+}
+
+impl PointLight {
+    pub fn new(radius: f64, colour: (u8, u8, u8)) -> Self {
+        PointLight { radius, colour }
+    }
+}
+
+// a light placed on the picture: its center in pixels up from the bottom left, and its radius in pixels
+pub type Glow = ((i32, i32), u32, (u8, u8, u8));
+
+// how much light is left at a distance, as a fraction of the radius; it eases off to nothing at the edge
+pub fn falloff(distance: f64) -> f64 {
+    let remaining = (1.0 - distance * distance).max(0.0);
+    remaining * remaining
+}
+
+// the level of light everything is seen in where no light reaches, from 0 for none at all to 1 for broad daylight
+pub fn ambient_light(darkness: f64) -> (u8, u8, u8) {
+    let level = (255.0 * (1.0 - darkness.clamp(0.0, 1.0))).round() as u8;
+    (level, level, level)
+}
+
+// a single light, bright in the middle, for backends that add lights up by drawing it stretched over each one
+pub fn glow_image(size: u32) -> RgbaImage {
+    let half = size as f64 / 2.0;
+    RgbaImage::from_fn(size, size, |x, y| {
+        let distance = ((x as f64 + 0.5 - half).powi(2) + (y as f64 + 0.5 - half).powi(2)).sqrt() / half;
+        let level = (255.0 * falloff(distance)).round() as u8;
+        Rgba([level, level, level, 255])
+    })
+}
+
+// the ambient light with every glow added on top, ready to multiply the picture by
+pub fn light_map(width: u32, height: u32, ambient: (u8, u8, u8), glows: &[Glow]) -> RgbImage {
+    let (r, g, b) = ambient;
+    let mut map = RgbImage::from_pixel(width, height, Rgb([r, g, b]));
+    for ((x, y), radius, (r, g, b)) in glows {
+        let (center_x, center_y) = (*x as f64, height as f64 - *y as f64);
+        let radius = *radius as i32;
+        let top = height as i32 - y;
+        for row in (top - radius).max(0)..(top + radius).min(height as i32) {
+            for column in (x - radius).max(0)..(x + radius).min(width as i32) {
+                let distance = ((column as f64 + 0.5 - center_x).powi(2) + (row as f64 + 0.5 - center_y).powi(2)).sqrt() / radius as f64;
+                let strength = falloff(distance);
+                let Rgb([mr, mg, mb]) = map.get_pixel_mut(column as u32, row as u32);
+                *mr = (*mr as f64 + *r as f64 * strength).min(255.0) as u8;
+                *mg = (*mg as f64 + *g as f64 * strength).min(255.0) as u8;
+                *mb = (*mb as f64 + *b as f64 * strength).min(255.0) as u8;
+            }
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fade_from_full_to_nothing_at_the_edge() {
+        assert_eq!(falloff(0.0), 1.0);
+        assert!(falloff(0.5) > 0.0 && falloff(0.5) < 1.0);
+        assert_eq!(falloff(1.0), 0.0);
+        assert_eq!(falloff(2.0), 0.0);
+    }
+
+    #[test]
+    fn should_darken_ambient_light() {
+        assert_eq!(ambient_light(0.0), (255, 255, 255));
+        assert_eq!(ambient_light(0.8), (51, 51, 51));
+        assert_eq!(ambient_light(3.0), (0, 0, 0));
+    }
+
+    #[test]
+    fn should_add_lights_to_the_ambient_level() {
+        let map = light_map(20, 10, (10, 10, 10), &[((5, 5), 4, (200, 100, 0)), ((6, 5), 4, (200, 100, 0))]);
+
+        assert_eq!(map.get_pixel(19, 0), &Rgb([10, 10, 10]));
+        let Rgb([r, g, b]) = *map.get_pixel(5, 4);
+        assert_eq!(r, 255);
+        assert!(g > 100 && g < 255);
+        assert_eq!(b, 10);
+    }
+
+    #[test]
+    fn should_put_lights_up_from_the_bottom() {
+        let map = light_map(10, 10, (0, 0, 0), &[((2, 1), 2, (255, 255, 255))]);
+
+        assert_ne!(map.get_pixel(2, 8), &Rgb([0, 0, 0]));
+        assert_eq!(map.get_pixel(2, 1), &Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn should_make_a_glow_brightest_in_the_middle() {
+        let glow = glow_image(16);
+
+        assert!(glow.get_pixel(8, 8)[0] > 240);
+        assert_eq!(glow.get_pixel(0, 0)[0], 0);
+        assert_eq!(glow.get_pixel(8, 8)[3], 255);
+    }
+}
@@ -0,0 +1,212 @@
+use std::f64::consts::PI;
+use std::ops::Range;
+
+const WAVE_HEIGHT: f64 = 2.0;
+const SHAKE_DISTANCE: f64 = 1.0;
+const TYPEWRITER_RATE: f64 = 15.0;
+const POP_LENGTH: f64 = 0.25;
+const POP_STAGGER: f64 = 0.04;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextEffect {
+    Wave,
+    Shake,
+    Rainbow,
+    Typewriter,
+    Pop,
+}
+
+// how one character is drawn once every effect on it has had its say; offsets are in pixels
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphStyle {
+    pub offset: (f64, f64),
+    pub scale: f64,
+    pub colour: Option<(u8, u8, u8)>,
+    pub visible: bool,
+}
+
+impl GlyphStyle {
+    pub fn new(colour: Option<(u8, u8, u8)>) -> Self {
+        GlyphStyle { offset: (0.0, 0.0), scale: 1.0, colour, visible: true }
+    }
+}
+
+impl TextEffect {
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "wave" => Some(TextEffect::Wave),
+            "shake" => Some(TextEffect::Shake),
+            "rainbow" => Some(TextEffect::Rainbow),
+            "type" => Some(TextEffect::Typewriter),
+            "pop" => Some(TextEffect::Pop),
+            _ => None,
+        }
+    }
+
+    // position counts characters from the start of the effect, so each run animates from its own beginning
+    pub fn apply(&self, style: GlyphStyle, position: usize, time: f64) -> GlyphStyle {
+        let position = position as f64;
+        match self {
+            TextEffect::Wave => {
+                let (dx, dy) = style.offset;
+                GlyphStyle { offset: (dx, dy + WAVE_HEIGHT * (time * 8.0 - position * 0.6).sin()), ..style }
+            }
+            TextEffect::Shake => {
+                let frame = (time * 20.0).floor() as i64;
+                let (dx, dy) = style.offset;
+                let jitter = |salt: u64| (jitter(position as u64, frame, salt) * 2.0 - 1.0) * SHAKE_DISTANCE;
+                GlyphStyle { offset: (dx + jitter(1).round(), dy + jitter(2).round()), ..style }
+            }
+            TextEffect::Rainbow => GlyphStyle { colour: Some(hue((time * 0.5 + position * 0.08).rem_euclid(1.0))), ..style },
+            TextEffect::Typewriter => GlyphStyle { visible: style.visible && position < time * TYPEWRITER_RATE, ..style },
+            TextEffect::Pop => {
+                let progress = ((time - position * POP_STAGGER) / POP_LENGTH).clamp(0.0, 1.0);
+                GlyphStyle { scale: style.scale * overshoot(progress), ..style }
+            }
+        }
+    }
+}
+
+// pulls <tag>...</tag> pairs out of the text; tags it doesn't know are left in as they are,
+// and an unclosed tag runs to the end
+pub fn parse(markup: &str) -> (String, Vec<(Range<usize>, TextEffect)>) {
+    let mut text = String::new();
+    let mut length = 0;
+    let mut open: Vec<(TextEffect, usize)> = Vec::new();
+    let mut effects = Vec::new();
+    let mut rest = markup;
+    while !rest.is_empty() {
+        if let Some((tag, after)) = tag(rest) {
+            match tag {
+                Tag::Open(effect) => open.push((effect, length)),
+                Tag::Close(effect) => {
+                    if let Some(index) = open.iter().rposition(|(opened, _)| *opened == effect) {
+                        let (_, start) = open.remove(index);
+                        effects.push((start..length, effect));
+                    }
+                }
+            }
+            rest = after;
+        } else {
+            let ch = rest.chars().next().unwrap();
+            text.push(ch);
+            length += 1;
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+    effects.extend(open.into_iter().map(|(effect, start)| (start..length, effect)));
+    (text, effects)
+}
+
+enum Tag {
+    Open(TextEffect),
+    Close(TextEffect),
+}
+
+fn tag(text: &str) -> Option<(Tag, &str)> {
+    let inner = text.strip_prefix('<')?;
+    let end = inner.find('>')?;
+    let (name, after) = (&inner[..end], &inner[end + 1..]);
+    match name.strip_prefix('/') {
+        Some(name) => TextEffect::from_tag(name).map(|effect| (Tag::Close(effect), after)),
+        None => TextEffect::from_tag(name).map(|effect| (Tag::Open(effect), after)),
+    }
+}
+
+fn jitter(position: u64, frame: i64, salt: u64) -> f64 {
+    let mut hash = position.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (frame as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F) ^ salt;
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(0x1656_67B1_9E37_79F9);
+    hash ^= hash >> 32;
+    (hash % 1000) as f64 / 999.0
+}
+
+// eases out past full size and settles back, starting from nothing
+fn overshoot(progress: f64) -> f64 {
+    let (c1, c3) = (1.70158, 2.70158);
+    let t = progress - 1.0;
+    1.0 + c3 * t * t * t + c1 * t * t
+}
+
+fn hue(hue: f64) -> (u8, u8, u8) {
+    let channel = |offset: f64| {
+        let value = (((hue + offset) * 2.0 * PI).cos() + 1.0) / 2.0;
+        (value * 255.0).round() as u8
+    };
+    (channel(0.0), channel(-1.0 / 3.0), channel(1.0 / 3.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_strip_known_tags() {
+        let (text, effects) = parse("<wave>Fruit</wave> <pop>Salad!</pop>");
+
+        assert_eq!(text, "Fruit Salad!");
+        assert_eq!(effects, vec![(0..5, TextEffect::Wave), (6..12, TextEffect::Pop)]);
+    }
+
+    #[test]
+    fn should_nest_and_leave_unknown_tags_alone() {
+        let (text, effects) = parse("<rainbow>a<b><shake>c</rainbow>d");
+
+        assert_eq!(text, "a<b>cd");
+        assert_eq!(effects, vec![(0..5, TextEffect::Rainbow), (4..6, TextEffect::Shake)]);
+    }
+
+    #[test]
+    fn should_count_characters_rather_than_bytes() {
+        let (text, effects) = parse("£<pop>5</pop>");
+
+        assert_eq!(text, "£5");
+        assert_eq!(effects, vec![(1..2, TextEffect::Pop)]);
+    }
+
+    #[test]
+    fn should_reveal_one_character_at_a_time() {
+        let visible = |position: usize, time: f64| TextEffect::Typewriter.apply(GlyphStyle::new(None), position, time).visible;
+
+        assert!(!visible(0, 0.0));
+        assert!(visible(0, 0.01));
+        assert!(visible(2, 0.2));
+        assert!(!visible(3, 0.2));
+    }
+
+    #[test]
+    fn should_pop_up_past_full_size_and_settle() {
+        let scale = |position: usize, time: f64| TextEffect::Pop.apply(GlyphStyle::new(None), position, time).scale;
+
+        assert!(scale(0, 0.0).abs() < 1e-9);
+        assert!(scale(0, POP_LENGTH * 0.8) > 1.0);
+        assert!((scale(0, POP_LENGTH) - 1.0).abs() < 1e-9);
+        assert!(scale(5, POP_LENGTH) < 1.0);
+    }
+
+    #[test]
+    fn should_move_characters_by_whole_pixels_when_shaking() {
+        for time in [0.0, 0.13, 0.77] {
+            let (dx, dy) = TextEffect::Shake.apply(GlyphStyle::new(None), 3, time).offset;
+            assert!(dx.abs() <= SHAKE_DISTANCE && dy.abs() <= SHAKE_DISTANCE);
+            assert_eq!((dx.fract(), dy.fract()), (0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn should_wave_along_the_text() {
+        let offset = |position: usize| TextEffect::Wave.apply(GlyphStyle::new(None), position, 0.5).offset.1;
+
+        assert!(offset(0).abs() <= WAVE_HEIGHT);
+        assert_ne!(offset(0), offset(1));
+    }
+
+    #[test]
+    fn should_cycle_through_colours() {
+        let colour = |time: f64| TextEffect::Rainbow.apply(GlyphStyle::new(Some((1, 2, 3))), 0, time).colour;
+
+        assert_eq!(colour(0.0), Some((255, 64, 64)));
+        assert_ne!(colour(0.0), colour(0.5));
+        assert_eq!(colour(0.0), colour(2.0));
+    }
+}
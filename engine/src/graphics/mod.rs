@@ -1,2 +1,24 @@
+pub mod animation;
+pub mod aseprite;
+pub mod atlas;
+pub mod backend;
+pub mod batch;
+pub mod camera;
+pub mod capture;
+pub mod display;
+pub mod effects;
+pub mod font;
+pub mod frames;
+#[cfg(test)]
+mod golden;
+pub mod layer;
+pub mod lighting;
+pub mod markup;
+pub mod palette;
+pub mod parallax;
+pub mod postprocess;
 pub mod renderer;
-pub mod sprite;
\ No newline at end of file
+pub mod software;
+pub mod sprite;
+pub mod transition;
+pub mod viewport;
\ No newline at end of file
@@ -0,0 +1,84 @@
+use image::{Rgba, RgbaImage};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette(pub Vec<Rgba<u8>>);
+
+impl Palette {
+    pub fn new(colours: &[(u8, u8, u8)]) -> Self {
+        Palette(colours.iter().map(|(r, g, b)| Rgba([*r, *g, *b, 255])).collect())
+    }
+
+    // reads a palette laid out left to right along the top row of an image
+    pub fn from_image(image: &RgbaImage) -> Self {
+        Palette((0..image.width()).map(|x| *image.get_pixel(x, 0)).collect())
+    }
+
+    pub fn index_of(&self, Rgba([r, g, b, _]): &Rgba<u8>) -> Option<usize> {
+        let Palette(colours) = self;
+        colours.iter().position(|Rgba([pr, pg, pb, _])| (pr, pg, pb) == (r, g, b))
+    }
+}
+
+// recolours every pixel found in one palette with the matching entry of the other, keeping its alpha
+pub fn swap_palette(image: &RgbaImage, from: &Palette, to: &Palette) -> RgbaImage {
+    let Palette(to_colours) = to;
+    let mut swapped = image.clone();
+    for pixel in swapped.pixels_mut() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        if let Some(Rgba([r, g, b, _])) = from.index_of(pixel).and_then(|index| to_colours.get(index)) {
+            *pixel = Rgba([*r, *g, *b, pixel[3]]);
+        }
+    }
+    swapped
+}
+
+pub fn variant_name(sheet: &str, palette: &str) -> String {
+    format!("{sheet}#{palette}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blue() -> Palette {
+        Palette::new(&[(42, 75, 120), (91, 110, 225)])
+    }
+
+    fn red() -> Palette {
+        Palette::new(&[(117, 46, 12), (175, 86, 61)])
+    }
+
+    #[test]
+    fn should_swap_matching_colours() {
+        let mut image = RgbaImage::new(3, 1);
+        image.put_pixel(0, 0, Rgba([91, 110, 225, 255]));
+        image.put_pixel(1, 0, Rgba([42, 75, 120, 128]));
+        image.put_pixel(2, 0, Rgba([255, 255, 255, 255]));
+
+        let swapped = swap_palette(&image, &blue(), &red());
+
+        assert_eq!(swapped.get_pixel(0, 0), &Rgba([175, 86, 61, 255]));
+        assert_eq!(swapped.get_pixel(1, 0), &Rgba([117, 46, 12, 128]));
+        assert_eq!(swapped.get_pixel(2, 0), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn should_leave_colours_without_a_replacement() {
+        let image = RgbaImage::from_pixel(1, 1, Rgba([91, 110, 225, 255]));
+
+        let swapped = swap_palette(&image, &blue(), &Palette::new(&[(0, 0, 0)]));
+
+        assert_eq!(swapped.get_pixel(0, 0), &Rgba([91, 110, 225, 255]));
+    }
+
+    #[test]
+    fn should_read_palettes_from_images() {
+        let mut image = RgbaImage::new(2, 2);
+        image.put_pixel(0, 0, Rgba([42, 75, 120, 255]));
+        image.put_pixel(1, 0, Rgba([91, 110, 225, 255]));
+
+        assert_eq!(Palette::from_image(&image), blue());
+    }
+}
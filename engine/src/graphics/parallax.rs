@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use super::camera::Camera;
+use super::sprite::Sprite;
+
+#[derive(Clone)]
+pub struct ParallaxLayer {
+    pub tiles: Vec<(f64, f64, Sprite)>,
+    pub width: f64,
+    pub height: f64,
+    pub factor: (f64, f64),
+    pub scroll: (f64, f64),
+    pub repeat: bool,
+    pub offset: (f64, f64),
+    scrolled: (f64, f64),
+}
+
+impl ParallaxLayer {
+    pub fn new(width: f64, height: f64) -> Self {
+        ParallaxLayer {
+            tiles: Vec::new(),
+            width,
+            height,
+            factor: (1.0, 1.0),
+            scroll: (0.0, 0.0),
+            repeat: false,
+            offset: (0.0, 0.0),
+            scrolled: (0.0, 0.0),
+        }
+    }
+
+    pub fn with_factor(mut self, x: f64, y: f64) -> Self {
+        self.factor = (x, y);
+        self
+    }
+
+    pub fn with_scroll(mut self, x: f64, y: f64) -> Self {
+        self.scroll = (x, y);
+        self
+    }
+
+    pub fn with_offset(mut self, x: f64, y: f64) -> Self {
+        self.offset = (x, y);
+        self
+    }
+
+    pub fn repeating(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+
+    pub fn with_tile(mut self, x: f64, y: f64, sprite: Sprite) -> Self {
+        self.tiles.push((x, y, sprite));
+        self
+    }
+
+    pub fn update(&mut self, dt: &Duration) {
+        let (dx, dy) = self.scroll;
+        let (sx, sy) = self.scrolled;
+        let dt = dt.as_secs_f64();
+        self.scrolled = if self.repeat {
+            ((sx + dx * dt) % self.width, (sy + dy * dt) % self.height)
+        } else {
+            (sx + dx * dt, sy + dy * dt)
+        };
+    }
+
+    pub fn origin(&self, camera: &Camera) -> (f64, f64) {
+        let (fx, fy) = self.factor;
+        let (sx, sy) = self.scrolled;
+        (camera.x * (1.0 - fx) + sx, camera.y * (1.0 - fy) + sy)
+    }
+
+    pub fn visible_tiles(&self, camera: &Camera) -> Vec<(f64, f64, &Sprite)> {
+        let (origin_x, origin_y) = self.origin(camera);
+        let mut visible = Vec::new();
+        for copy_x in self.copies(origin_x, camera.x, camera.width, self.width) {
+            for copy_y in self.copies(origin_y, camera.y, camera.height, self.height) {
+                for (x, y, sprite) in &self.tiles {
+                    let (x, y) = (copy_x + x, copy_y + y);
+                    if camera.can_see(x, y, 1.0, 1.0) {
+                        visible.push((x, y, sprite));
+                    }
+                }
+            }
+        }
+        visible
+    }
+
+    fn copies(&self, origin: f64, view: f64, view_size: f64, size: f64) -> Vec<f64> {
+        if !self.repeat || size <= 0.0 {
+            return vec![origin];
+        }
+        let first = ((view - origin) / size).floor() as i32;
+        let last = ((view + view_size - origin) / size).floor() as i32;
+        (first..=last).map(|copy| origin + copy as f64 * size).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::layer::Layer;
+
+    fn layer() -> ParallaxLayer {
+        ParallaxLayer::new(4.0, 2.0)
+            .with_tile(0.0, 0.0, Sprite::new(0, 0, Layer::Background, "Sky"))
+            .with_tile(3.0, 1.0, Sprite::new(1, 0, Layer::Background, "Sky"))
+    }
+
+    fn positions(layer: &ParallaxLayer, camera: &Camera) -> Vec<(f64, f64)> {
+        layer.visible_tiles(camera).iter().map(|(x, y, _)| (*x, *y)).collect()
+    }
+
+    #[test]
+    fn should_move_with_camera_at_full_parallax() {
+        let mut camera = Camera::new(4.0, 2.0);
+        camera.x = 10.0;
+
+        assert_eq!(layer().origin(&camera), (0.0, 0.0));
+    }
+
+    #[test]
+    fn should_lag_behind_camera_at_reduced_parallax() {
+        let mut camera = Camera::new(4.0, 2.0);
+        camera.x = 10.0;
+        camera.y = 4.0;
+
+        assert_eq!(layer().with_factor(0.5, 0.0).origin(&camera), (5.0, 4.0));
+    }
+
+    #[test]
+    fn should_auto_scroll_and_wrap_when_repeating() {
+        let mut layer = layer().with_scroll(-1.0, 0.0).repeating();
+        layer.update(&Duration::from_secs(5));
+
+        assert_eq!(layer.origin(&Camera::new(4.0, 2.0)), (-1.0, 0.0));
+    }
+
+    #[test]
+    fn should_repeat_to_cover_the_view() {
+        let mut camera = Camera::new(6.0, 2.0);
+        camera.x = 2.0;
+
+        assert_eq!(positions(&layer().repeating(), &camera), vec![(3.0, 1.0), (4.0, 0.0), (7.0, 1.0)]);
+    }
+
+    #[test]
+    fn should_only_draw_once_without_repeat() {
+        let mut camera = Camera::new(6.0, 2.0);
+        camera.x = 2.0;
+
+        assert_eq!(positions(&layer(), &camera), vec![(3.0, 1.0)]);
+    }
+}
@@ -0,0 +1,189 @@
+use image::{Rgba, RgbaImage};
+
+// the darkened rows keep this much of their light, out of 255
+const SCANLINE_LEVEL: u32 = 170;
+// each column lets its own channel through and dims the other two to this
+const MASK_LEVEL: u32 = 190;
+const BLOOM_THRESHOLD: u8 = 180;
+const BLOOM_RADIUS: i32 = 2;
+const BLOOM_STRENGTH: f64 = 0.6;
+const CURVATURE: f64 = 0.06;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostFilter {
+    Bloom,
+    ShadowMask,
+    Scanlines,
+    Curvature,
+}
+
+impl PostFilter {
+    // the order they're applied in, whatever order they were chosen in: the glow has to be in the picture
+    // before the mask and scanlines cut it up, and bending the screen comes last
+    pub const ALL: [PostFilter; 4] = [PostFilter::Bloom, PostFilter::ShadowMask, PostFilter::Scanlines, PostFilter::Curvature];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bloom" => Some(PostFilter::Bloom),
+            "shadow-mask" => Some(PostFilter::ShadowMask),
+            "scanlines" => Some(PostFilter::Scanlines),
+            "curvature" => Some(PostFilter::Curvature),
+            _ => None,
+        }
+    }
+}
+
+pub fn post_process(image: &mut RgbaImage, filters: &[PostFilter]) {
+    for filter in PostFilter::ALL.iter().filter(|filter| filters.contains(filter)) {
+        match filter {
+            PostFilter::Bloom => bloom(image),
+            PostFilter::ShadowMask => shadow_mask(image),
+            PostFilter::Scanlines => scanlines(image),
+            PostFilter::Curvature => *image = curvature(image),
+        }
+    }
+}
+
+pub fn scanlines(image: &mut RgbaImage) {
+    for (_, y, Rgba([r, g, b, _])) in image.enumerate_pixels_mut() {
+        if y % 2 == 1 {
+            for channel in [r, g, b] {
+                *channel = (*channel as u32 * SCANLINE_LEVEL / 255) as u8;
+            }
+        }
+    }
+}
+
+// an aperture grille: columns of red, green and blue stripes, one pixel each
+pub fn shadow_mask(image: &mut RgbaImage) {
+    for (x, _, Rgba(pixel)) in image.enumerate_pixels_mut() {
+        for (channel, value) in pixel.iter_mut().take(3).enumerate() {
+            if channel != x as usize % 3 {
+                *value = (*value as u32 * MASK_LEVEL / 255) as u8;
+            }
+        }
+    }
+}
+
+// only pixels bright enough to glow are blurred, and the blur is added back over the picture
+pub fn bloom(image: &mut RgbaImage) {
+    let (width, height) = (image.width() as i32, image.height() as i32);
+    let bright: Vec<[f64; 3]> = image.pixels()
+        .map(|Rgba([r, g, b, _])| if *r.max(g).max(b) >= BLOOM_THRESHOLD { [*r as f64, *g as f64, *b as f64] } else { [0.0; 3] })
+        .collect();
+    let across = box_blur(&bright, width, height, (1, 0));
+    let blurred = box_blur(&across, width, height, (0, 1));
+    for (Rgba(pixel), glow) in image.pixels_mut().zip(blurred) {
+        for (value, glow) in pixel.iter_mut().zip(glow) {
+            *value = (*value as f64 + glow * BLOOM_STRENGTH).min(255.0) as u8;
+        }
+    }
+}
+
+fn box_blur(pixels: &[[f64; 3]], width: i32, height: i32, (dx, dy): (i32, i32)) -> Vec<[f64; 3]> {
+    let samples = (2 * BLOOM_RADIUS + 1) as f64;
+    (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| {
+        let mut sum = [0.0; 3];
+        for offset in -BLOOM_RADIUS..=BLOOM_RADIUS {
+            let (sx, sy) = (x + offset * dx, y + offset * dy);
+            if sx >= 0 && sy >= 0 && sx < width && sy < height {
+                let sample = pixels[(sy * width + sx) as usize];
+                sum.iter_mut().zip(sample).for_each(|(total, value)| *total += value);
+            }
+        }
+        sum.map(|total| total / samples)
+    }).collect()
+}
+
+// barrel distortion: each pixel looks a little further out from the middle than it is, more so towards the
+// corners, and anything that looks past the edge of the picture is left empty
+pub fn curvature(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let u = (x as f64 + 0.5) / width * 2.0 - 1.0;
+        let v = (y as f64 + 0.5) / height * 2.0 - 1.0;
+        let bend = 1.0 + CURVATURE * (u * u + v * v);
+        let (source_x, source_y) = (((u * bend + 1.0) / 2.0 * width).floor(), ((v * bend + 1.0) / 2.0 * height).floor());
+        if source_x < 0.0 || source_y < 0.0 || source_x >= width || source_y >= height {
+            Rgba([0, 0, 0, 0])
+        } else {
+            *image.get_pixel(source_x as u32, source_y as u32)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grey(width: u32, height: u32, level: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([level, level, level, 255]))
+    }
+
+    #[test]
+    fn should_darken_every_other_row() {
+        let mut image = grey(2, 4, 255);
+        scanlines(&mut image);
+
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(image.get_pixel(0, 1), &Rgba([170, 170, 170, 255]));
+        assert_eq!(image.get_pixel(1, 2), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn should_stripe_columns_red_green_and_blue() {
+        let mut image = grey(4, 1, 255);
+        shadow_mask(&mut image);
+
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 190, 190, 255]));
+        assert_eq!(image.get_pixel(1, 0), &Rgba([190, 255, 190, 255]));
+        assert_eq!(image.get_pixel(2, 0), &Rgba([190, 190, 255, 255]));
+        assert_eq!(image.get_pixel(3, 0), &Rgba([255, 190, 190, 255]));
+    }
+
+    #[test]
+    fn should_spread_bright_pixels_only() {
+        let mut image = grey(7, 7, 20);
+        image.put_pixel(3, 3, Rgba([255, 200, 0, 255]));
+        let mut dark = image.clone();
+        dark.put_pixel(3, 3, Rgba([100, 100, 100, 255]));
+        let untouched = dark.clone();
+
+        bloom(&mut image);
+        bloom(&mut dark);
+
+        assert!(image.get_pixel(4, 4)[0] > 20);
+        assert!(image.get_pixel(4, 4)[1] > 20);
+        assert_eq!(image.get_pixel(0, 0), &Rgba([20, 20, 20, 255]));
+        assert_eq!(dark, untouched);
+    }
+
+    #[test]
+    fn should_bend_the_edges_and_keep_the_middle() {
+        let mut image = grey(40, 30, 0);
+        image.put_pixel(20, 15, Rgba([255, 0, 0, 255]));
+        let bent = curvature(&image);
+
+        assert_eq!(bent.get_pixel(20, 15), &Rgba([255, 0, 0, 255]));
+        assert_eq!(bent.get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_apply_filters_in_a_fixed_order() {
+        let mut masked_first = grey(3, 2, 255);
+        post_process(&mut masked_first, &[PostFilter::Scanlines, PostFilter::ShadowMask]);
+        let mut expected = grey(3, 2, 255);
+        shadow_mask(&mut expected);
+        scanlines(&mut expected);
+
+        assert_eq!(masked_first, expected);
+    }
+
+    #[test]
+    fn should_leave_the_picture_alone_without_filters() {
+        let mut image = grey(3, 2, 99);
+        post_process(&mut image, &[]);
+
+        assert_eq!(image, grey(3, 2, 99));
+    }
+}
@@ -1,15 +1,33 @@
-use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::time::Instant;
+use std::ops::Range;
+use std::time::{Duration, Instant};
 
-use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::{BlendMode, TargetRenderError, Texture, TextureCreator, TextureValueError, WindowCanvas};
+use image::{Rgba, RgbaImage};
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
 use sdl2::video::WindowContext;
 
 use component_derive::Variable;
 
-use super::sprite::{Sprite, SpriteSheet};
+use crate::error::EngineError;
+use crate::profiler::{Profiled, Profiler};
+
+use super::backend::RenderBackend;
+use super::batch::{Placed, SpriteBatch};
+use super::camera::Camera;
+use super::capture::{Captured, FrameRecorder};
+use super::display::{target_rect, view_position, Displayed};
+use super::font::{Font, DEFAULT_FONT};
+use super::lighting::{glow_image, PointLight};
+use super::postprocess::{post_process, PostFilter};
+use super::markup::{parse, GlyphStyle, TextEffect};
+use super::sprite::{texture_from_image, Sprite, SpriteSheet};
+use super::transition::Transition;
+use super::viewport::Viewport;
+
+const GLOW_SIZE: u32 = 64;
 
 pub mod align {
     pub const CENTER: u8 = 0b_0000_0001;
@@ -23,22 +41,85 @@ pub mod align {
 #[derive(Clone, Variable)]
 pub struct Text {
     pub text: String,
-    pub justification: u8
+    pub justification: u8,
+    pub font: String,
+    pub wrap: Option<f64>,
+    pub colours: Vec<(Range<usize>, (u8, u8, u8))>,
+    pub effects: Vec<(Range<usize>, TextEffect)>,
+    pub time: f64,
+}
+
+impl Text {
+    // effects are marked up in the text with tags such as <wave>this</wave>
+    pub fn new(markup: &str, justification: u8) -> Self {
+        let (text, effects) = parse(markup);
+        Text { effects, ..Text::plain(&text, justification) }
+    }
+
+    // for text that shouldn't be read as markup, like names players have typed in
+    pub fn plain(text: &str, justification: u8) -> Self {
+        Text {
+            text: text.to_string(),
+            justification,
+            font: DEFAULT_FONT.to_string(),
+            wrap: None,
+            colours: Vec::new(),
+            effects: Vec::new(),
+            time: 0.0,
+        }
+    }
+
+    pub fn in_font(self, font: &str) -> Self {
+        Text { font: font.to_string(), ..self }
+    }
+
+    pub fn wrapped(self, width: f64) -> Self {
+        Text { wrap: Some(width), ..self }
+    }
+
+    // colours the characters in the range; later runs win where they overlap
+    pub fn coloured(mut self, range: Range<usize>, r: u8, g: u8, b: u8) -> Self {
+        self.colours.push((range, (r, g, b)));
+        self
+    }
+
+    // how far through its effects the text is, usually the seconds since it appeared
+    pub fn at_time(self, time: f64) -> Self {
+        Text { time, ..self }
+    }
+
+    pub fn colour_at(&self, index: usize) -> Option<(u8, u8, u8)> {
+        self.colours.iter().rev().find(|(range, _)| range.contains(&index)).map(|(_, colour)| *colour)
+    }
+
+    pub fn style_at(&self, index: usize) -> GlyphStyle {
+        self.effects.iter()
+            .filter(|(range, _)| range.contains(&index))
+            .fold(GlyphStyle::new(self.colour_at(index)), |style, (range, effect)| effect.apply(style, index - range.start, self.time))
+    }
 }
 
 pub struct Renderer<'a> 
 {
     canvas: WindowCanvas,
     surface: Texture<'a>,
-    spritesheets: &'a HashMap<String, SpriteSheet<'a>>,
-    batch: Vec<(Sprite, (i32, i32))>,
+    held: Texture<'a>,
+    composite: Texture<'a>,
+    light_map: Texture<'a>,
+    glow: Texture<'a>,
+    spritesheets: HashMap<String, SpriteSheet<'a>>,
+    batch: SpriteBatch,
     source_rect: Rect,
     target_rect: Rect,
-    text_width: f64,
-    text_height: f64,
+    integer_scaling: bool,
+    post_filters: Vec<PostFilter>,
+    composed: bool,
     tile_width: f64,
     tile_height: f64,
-    fps_counter: FpsCounter
+    fps_counter: FpsCounter,
+    profiler: Profiler,
+    recorder: FrameRecorder,
+    last_frame: Instant,
 }
 
 impl <'a> Renderer<'a>
@@ -46,165 +127,425 @@ impl <'a> Renderer<'a>
     pub fn new(
         canvas: WindowCanvas, 
         texture_creator: &'a TextureCreator<WindowContext>, 
-        spritesheets: &'a HashMap<String, SpriteSheet<'a>>,
+        spritesheets: HashMap<String, SpriteSheet<'a>>,
         columns: u32,
         rows: u32,
         tile_width: u32,
         tile_height: u32,
-    ) -> Result<Self, TextureValueError>
+    ) -> Result<Self, EngineError>
     {
         let width = columns * tile_width;
         let height = rows * tile_height;
         let source_rect = Rect::new(0, 0, width, height);
-        let target_rect = calculate_target_rect(&canvas, width, height);
-        let mut surface: Texture<'a> = texture_creator.create_texture_target(None, width, height)?;
-        let text_width = 8.0 / tile_width as f64;
-        let text_height = 8.0 / tile_height as f64;
+        let target_rect = calculate_target_rect(&canvas, width, height, false)?;
+        let mut surface: Texture<'a> = texture_creator.create_texture_target(None, width, height)
+            .map_err(|err| EngineError::Render(err.to_string()))?;
+        let mut held: Texture<'a> = texture_creator.create_texture_target(None, width, height)
+            .map_err(|err| EngineError::Render(err.to_string()))?;
+        let mut composite: Texture<'a> = texture_creator.create_texture_target(None, width, height)
+            .map_err(|err| EngineError::Render(err.to_string()))?;
+        let mut light_map: Texture<'a> = texture_creator.create_texture_target(None, width, height)
+            .map_err(|err| EngineError::Render(err.to_string()))?;
+        let mut glow = texture_from_image(texture_creator, &glow_image(GLOW_SIZE))?;
         let fps_counter = FpsCounter::new(30);
+        let profiler = Profiler::new(120);
+        let recorder = FrameRecorder::new(Duration::from_secs(5), Duration::from_millis(1000 / 15)).with_enabled(false);
 
         surface.set_blend_mode(BlendMode::Blend);
+        held.set_blend_mode(BlendMode::Blend);
+        composite.set_blend_mode(BlendMode::Blend);
+        light_map.set_blend_mode(BlendMode::Mod);
+        glow.set_blend_mode(BlendMode::Add);
         Ok(Renderer {
             canvas,
             surface,
+            held,
+            composite,
+            light_map,
+            glow,
             spritesheets,
             source_rect,
             target_rect,
-            batch : Vec::new(),
-            text_width,
-            text_height,
+            integer_scaling: false,
+            post_filters: Vec::new(),
+            composed: false,
+            batch: SpriteBatch::new(tile_width, tile_height),
             tile_width: tile_width as f64,
             tile_height: tile_height as f64,
-            fps_counter
+            fps_counter,
+            profiler,
+            recorder,
+            last_frame: Instant::now(),
         })
     }
 
-    pub fn draw_sprite(&mut self, sprite: &Sprite, x: f64, y: f64) {
-        self.batch.push((
-            sprite.clone(),
-            ((x * self.tile_width).round() as i32, (y * self.tile_height).round() as i32)));
+    pub fn with_integer_scaling(mut self, integer_scaling: bool) -> Result<Self, EngineError> {
+        self.integer_scaling = integer_scaling;
+        self.window_resized()?;
+        Ok(self)
     }
 
-    pub fn draw_text(&mut self, Text { text, justification }: &Text, x: f64, y: f64) {
-        let text_width = text.len() as f64 * self.text_width;
-        let mut current_x = match (justification & align::LEFT > 0, justification & align::RIGHT > 0) {
-            (true, false) => x,
-            (false, true) => x - text_width,
-            _ => x - (text_width / 2.0),
-        };
-
-        let y = match (justification & align::BOTTOM > 0, justification & align::TOP > 0) {
-            (true, false) => y,
-            (false, true) => y - self.text_height,
-            _ => y - (self.text_height / 2.0),
-        };
+    pub fn with_post_filters(self, post_filters: Vec<PostFilter>) -> Self {
+        Renderer { post_filters, ..self }
+    }
 
-        for ch in text.chars() {
-            let (tx, ty) = char_tile(ch);
-            self.batch.push((
-                Sprite::new(tx, ty, 2.0, "Text"),
-                ((current_x * self.tile_width).round() as i32, (y * self.tile_height).round() as i32),
-            )
-            );
-            current_x += self.text_width;
-        }
+    // off unless asked for, as every recorded frame has to be read back from the GPU
+    pub fn with_recording(self, recording: bool) -> Self {
+        Renderer { recorder: self.recorder.with_enabled(recording), ..self }
     }
 
-    fn draw_batch(&mut self) {
+    fn draw_batch(&mut self) -> Result<(), EngineError> {
         let height = self.source_rect.height() as i32;
-        self.batch.sort_by(|(sprite1, _), (sprite2, _)| compare(&sprite1.z, &sprite2.z));
-        let batch = &self.batch;
+        let ambient = self.batch.ambient_light();
+        if let Some(ambient) = ambient {
+            self.draw_light_map(ambient)?;
+        }
+        let fills = self.batch.fills().clone();
+        let lines = self.batch.lines().clone();
+        let runs = self.batch.runs();
+        let lit = runs.iter().position(|run| !run[0].0.layer.is_lit()).unwrap_or(runs.len());
         let spritesheets = &self.spritesheets;
-        self.canvas.with_texture_canvas(&mut self.surface, |c| { 
-            for (sprite, (x, y)) in batch {
-                let Sprite { flip_x, flip_y, tileset, .. } = sprite;
-                let spritesheet = spritesheets.get(tileset).unwrap();
-                let source_rect = spritesheet.source_rect(&sprite);
-                let corrected_y = (height - y) - source_rect.height() as i32;
-                if (false, false) == (*flip_x, *flip_y) {
-                    c.copy(
-                        &spritesheet.spritesheet,
-                        source_rect, 
-                        Rect::new(*x, corrected_y, source_rect.width(), source_rect.height()),
-                    ).unwrap();
-                } else {
-                    c.copy_ex(
-                        &spritesheet.spritesheet,
-                        source_rect, 
-                        Rect::new(*x, corrected_y, source_rect.width(), source_rect.height()),
-                        0.0,
-                        None,
-                        *flip_x,
-                        *flip_y
-                    ).unwrap();
+        let light_map = &self.light_map;
+        let mut result = Ok(());
+        self.canvas.with_texture_canvas(&mut self.surface, |c| {
+            result = runs[..lit].iter().try_for_each(|run| draw_run(c, spritesheets, run, height))
+            .and_then(|_| {
+                c.set_clip_rect(None);
+                match ambient {
+                    Some(_) => c.copy(light_map, None, None).map_err(EngineError::Render),
+                    None => Ok(()),
                 }
-            }
-        }).unwrap();
+            }).and_then(|_| {
+                runs[lit..].iter().try_for_each(|run| draw_run(c, spritesheets, run, height))
+            }).and_then(|_| {
+                c.set_clip_rect(None);
+                c.set_blend_mode(BlendMode::Blend);
+                fills.iter().try_for_each(|((x, y, width, fill_height), (r, g, b, a))| {
+                    c.set_draw_color(Color::RGBA(*r, *g, *b, *a));
+                    c.fill_rect(Rect::new(*x, height - y - *fill_height as i32, *width, *fill_height))
+                        .map_err(EngineError::Render)
+                })
+            }).and_then(|_| {
+                lines.iter().try_for_each(|((x1, y1), (x2, y2), (r, g, b, a))| {
+                    c.set_draw_color(Color::RGBA(*r, *g, *b, *a));
+                    c.draw_line(Point::new(*x1, height - 1 - y1), Point::new(*x2, height - 1 - y2))
+                        .map_err(EngineError::Render)
+                })
+            });
+        }).map_err(|err| EngineError::Render(err.to_string()))?;
 
         self.batch.clear();
+        result
     }
 
-    pub fn clear(&mut self) -> Result<(), TargetRenderError> {
-        self.canvas.with_texture_canvas(&mut self.surface, |c| {
-            c.set_draw_color(Color::from((0, 0, 0, 0)));
+    // the lights are added onto the ambient level on the GPU, each one the glow stretched over its radius
+    fn draw_light_map(&mut self, (r, g, b): (u8, u8, u8)) -> Result<(), EngineError> {
+        let height = self.source_rect.height() as i32;
+        let glows = self.batch.glows();
+        let glow = &mut self.glow;
+        let mut result = Ok(());
+        self.canvas.with_texture_canvas(&mut self.light_map, |c| {
+            c.set_draw_color(Color::RGB(r, g, b));
             c.clear();
-            ()
-        })
+            result = glows.iter().try_for_each(|((x, y), radius, (r, g, b))| {
+                glow.set_color_mod(*r, *g, *b);
+                let target = Rect::new(x - *radius as i32, height - y - *radius as i32, radius * 2, radius * 2);
+                c.copy(glow, None, target).map_err(EngineError::Render)
+            });
+        }).map_err(|err| EngineError::Render(err.to_string()))?;
+        result
     }
 
-    pub fn present(&mut self) -> Result<(), String>
-    where
-    {
-        self.draw_batch();
+    fn present_frame(&mut self, transition: Option<&Transition>) -> Result<(), EngineError> {
+        if self.profiler.overlay {
+            self.batch.set_camera(None);
+            self.draw_profiler_overlay();
+        }
 
+        let batch_start = Instant::now();
+        self.draw_batch()?;
+        self.profiler.record("render batch", batch_start.elapsed());
+
+        // during a transition both screens are put together first, so the filters and the recording see what's shown
+        if let Some(transition) = transition {
+            self.compose_transition(transition)?;
+        }
+        self.composed = transition.is_some();
+        let size = (self.source_rect.width(), self.source_rect.height());
+        let frame = match transition {
+            Some(_) => &mut self.composite,
+            None => &mut self.surface,
+        };
+
+        // the filters run on the CPU, so the frame has to come back from the GPU and go up again
+        if !self.post_filters.is_empty() {
+            let post_start = Instant::now();
+            let mut image = read_texture(&mut self.canvas, frame, size)?;
+            post_process(&mut image, &self.post_filters);
+            let pixels = packed_pixels(&image, frame.query().format)?;
+            frame.update(None, &pixels, image.width() as usize * 4)
+                .map_err(|err| EngineError::Render(err.to_string()))?;
+            self.profiler.record("post process", post_start.elapsed());
+        }
+
+        if self.recorder.tick(self.last_frame.elapsed()) {
+            let record_start = Instant::now();
+            let image = read_texture(&mut self.canvas, frame, size)?;
+            self.recorder.push(image);
+            self.profiler.record("record frame", record_start.elapsed());
+        }
+        self.last_frame = Instant::now();
+
+        let present_start = Instant::now();
         self.canvas.set_draw_color(Color::BLACK);
         self.canvas.clear();
-        self.canvas.copy(&mut self.surface, None, self.target_rect)?;
+        self.canvas.copy(frame, None, self.target_rect).map_err(EngineError::Render)?;
         self.canvas.present();
+        self.profiler.record("present", present_start.elapsed());
+
         self.fps_counter.on_frame();
+        self.profiler.end_frame();
         Ok(())
     }
+
+    // whatever was last put on screen, which during a transition is the two screens put together
+    fn read_presented(&mut self) -> Result<RgbaImage, EngineError> {
+        let size = (self.source_rect.width(), self.source_rect.height());
+        let frame = if self.composed { &mut self.composite } else { &mut self.surface };
+        read_texture(&mut self.canvas, frame, size)
+    }
+
+    // the held screen with the incoming one over it where the transition has reached, at the surface's size;
+    // the incoming screen replaces the outgoing one where it's shown, even where nothing was drawn on it
+    fn compose_transition(&mut self, transition: &Transition) -> Result<(), EngineError> {
+        let regions = transition.regions(self.source_rect.width(), self.source_rect.height());
+        let shade = transition.shade();
+        self.held.set_blend_mode(BlendMode::None);
+        self.surface.set_blend_mode(BlendMode::None);
+        let (held, surface) = (&self.held, &self.surface);
+        let mut result = Ok(());
+        self.canvas.with_texture_canvas(&mut self.composite, |c| {
+            result = c.copy(held, None, None)
+                .and_then(|_| regions.iter().try_for_each(|(x, y, width, height)| {
+                    let region = Rect::new(*x, *y, *width, *height);
+                    c.copy(surface, region, region)
+                }))
+                .and_then(|_| {
+                    if shade == 0 {
+                        return Ok(());
+                    }
+                    c.set_blend_mode(BlendMode::Blend);
+                    c.set_draw_color(Color::RGBA(0, 0, 0, shade));
+                    c.fill_rect(None)
+                })
+                .map_err(EngineError::Render);
+        }).map_err(|err| EngineError::Render(err.to_string()))?;
+        self.held.set_blend_mode(BlendMode::Blend);
+        self.surface.set_blend_mode(BlendMode::Blend);
+        result
+    }
+
+    fn draw_profiler_overlay(&mut self) {
+        let top = self.source_rect.height() as f64 / self.tile_height;
+        let line_height = self.batch.text_height();
+        for (line, text) in self.profiler.summary().into_iter().enumerate() {
+            let y = top - (line as f64 * line_height);
+            self.draw_text(&Text::new(&text, align::LEFT | align::TOP), 0.0, y);
+        }
+    }
+}
+
+impl <'a> RenderBackend for Renderer<'a> {
+    fn set_camera(&mut self, camera: Option<&Camera>) {
+        self.batch.set_camera(camera);
+    }
+
+    fn set_viewport(&mut self, viewport: Option<&Viewport>) {
+        self.batch.set_viewport(viewport);
+    }
+
+    fn add_font(&mut self, name: &str, font: Font) {
+        self.batch.add_font(name, font);
+    }
+
+    fn draw_sprite(&mut self, sprite: &Sprite, x: f64, y: f64) {
+        self.batch.push_sprite(sprite, x, y);
+    }
+
+    fn draw_text(&mut self, text: &Text, x: f64, y: f64) {
+        self.batch.push_text(text, x, y);
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, colour: (u8, u8, u8, u8)) {
+        self.batch.push_fill(x, y, width, height, colour);
+    }
+
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), colour: (u8, u8, u8, u8)) {
+        self.batch.push_line(from, to, colour);
+    }
+
+    fn draw_circle(&mut self, center: (f64, f64), radius: f64, colour: (u8, u8, u8, u8)) {
+        self.batch.push_circle(center, radius, colour);
+    }
+
+    fn set_ambient_light(&mut self, ambient: Option<(u8, u8, u8)>) {
+        self.batch.set_ambient_light(ambient);
+    }
+
+    fn draw_light(&mut self, x: f64, y: f64, light: &PointLight) {
+        self.batch.push_light(x, y, light);
+    }
+
+    fn clear(&mut self) -> Result<(), EngineError> {
+        self.canvas.with_texture_canvas(&mut self.surface, |c| {
+            c.set_draw_color(Color::from((0, 0, 0, 0)));
+            c.clear();
+            ()
+        }).map_err(|err| EngineError::Render(err.to_string()))
+    }
+
+    fn present(&mut self) -> Result<(), EngineError> {
+        self.present_frame(None)
+    }
+
+    fn hold_frame(&mut self) -> Result<(), EngineError> {
+        self.draw_batch()?;
+        let surface = &self.surface;
+        let mut result = Ok(());
+        self.canvas.with_texture_canvas(&mut self.held, |c| {
+            c.set_draw_color(Color::from((0, 0, 0, 0)));
+            c.clear();
+            result = c.copy(surface, None, None).map_err(EngineError::Render);
+        }).map_err(|err| EngineError::Render(err.to_string()))?;
+        result?;
+        self.clear()
+    }
+
+    fn present_transition(&mut self, transition: &Transition) -> Result<(), EngineError> {
+        self.present_frame(Some(transition))
+    }
+}
+
+impl <'a> Captured for Renderer<'a> {
+    fn screenshot(&mut self) -> Result<RgbaImage, EngineError> {
+        self.read_presented()
+    }
+
+    fn recorder(&mut self) -> &mut FrameRecorder {
+        &mut self.recorder
+    }
 }
 
-fn compare(a: &f64, b: &f64) -> Ordering {
-    match a.partial_cmp(b) {
-        Some(ord) => ord,
-        None => Ordering::Equal
+impl <'a> Displayed for Renderer<'a> {
+    fn window_resized(&mut self) -> Result<(), EngineError> {
+        self.target_rect = calculate_target_rect(&self.canvas, self.source_rect.width(), self.source_rect.height(), self.integer_scaling)?;
+        Ok(())
+    }
+
+    // mouse positions come in window coordinates, which are smaller than the output on high DPI displays
+    fn window_to_view(&self, x: i32, y: i32) -> Option<(f64, f64)> {
+        let (window_width, window_height) = self.canvas.window().size();
+        let (output_width, output_height) = self.canvas.output_size().ok()?;
+        let x = (x as i64 * output_width as i64 / window_width.max(1) as i64) as i32;
+        let y = (y as i64 * output_height as i64 / window_height.max(1) as i64) as i32;
+        view_position(
+            (self.target_rect.x(), self.target_rect.y(), self.target_rect.width(), self.target_rect.height()),
+            (self.source_rect.width(), self.source_rect.height()),
+            (self.tile_width as u32, self.tile_height as u32),
+            (x, y))
     }
 }
 
-fn calculate_target_rect(canvas: &WindowCanvas, width: u32, height: u32) -> Rect {
-    let (window_width, window_height) = canvas.window().size();
-    let x_scale = window_width as f64 / width as f64;
-    let y_scale = window_height as f64 / height as f64;
-    let scale = f64::min(x_scale, y_scale);
+impl <'a> Profiled for Renderer<'a> {
+    fn profiler(&mut self) -> &mut Profiler {
+        &mut self.profiler
+    }
+}
 
-    let scaled_width = (width as f64 * scale) as u32;
-    let x_offset = (window_width - scaled_width) / 2;
-    let scaled_height = (height as f64 * scale) as u32;
-    let y_offset = (window_height - scaled_height) / 2;
+// every sprite in a run shares a clip, so it's set once for the lot
+fn draw_run(c: &mut WindowCanvas, spritesheets: &HashMap<String, SpriteSheet>, run: &[Placed], height: i32) -> Result<(), EngineError> {
+    let sheet_name = run[0].0.sheet_name();
+    let spritesheet = spritesheets.get(&sheet_name)
+        .ok_or_else(|| EngineError::Render(format!("no sprite sheet named {sheet_name}")))?;
+    let mut texture = spritesheet.spritesheet.borrow_mut();
+    c.set_clip_rect(run[0].2.map(|(left, bottom, width, clip_height)| Rect::new(left, height - bottom - clip_height as i32, width, clip_height)));
+    run.iter().try_for_each(|(sprite, (x, y), _)| {
+        let Sprite { flip_x, flip_y, rotation, tint: (r, g, b, _), .. } = sprite;
+        let source_rect = spritesheet.source_rect(&sprite);
+        let corrected_y = (height - y) - source_rect.height() as i32;
+        texture.set_color_mod(*r, *g, *b);
+        texture.set_alpha_mod(sprite.alpha());
+        if (false, false) == (*flip_x, *flip_y) && !sprite.is_transformed() {
+            c.copy(
+                &texture,
+                source_rect, 
+                Rect::new(*x, corrected_y, source_rect.width(), source_rect.height()),
+            )
+        } else {
+            let ((left, top, width, height), (pivot_x, pivot_y)) =
+                sprite.placement(*x, corrected_y, source_rect.width(), source_rect.height());
+            c.copy_ex(
+                &texture,
+                source_rect, 
+                Rect::new(left.round() as i32, top.round() as i32, width.round() as u32, height.round() as u32),
+                *rotation,
+                Point::new(pivot_x.round() as i32, pivot_y.round() as i32),
+                *flip_x,
+                *flip_y
+            )
+        }.map_err(EngineError::Render)
+    })
+}
 
-    Rect::new(x_offset as i32, y_offset as i32, scaled_width, scaled_height)    
+fn read_texture(canvas: &mut WindowCanvas, texture: &mut Texture, (width, height): (u32, u32)) -> Result<RgbaImage, EngineError> {
+    let format = texture.query().format;
+    let mut result = Ok(Vec::new());
+    canvas.with_texture_canvas(texture, |c| {
+        result = c.read_pixels(None, format);
+    }).map_err(|err| EngineError::Render(err.to_string()))?;
+    let pixels = result.map_err(EngineError::Render)?;
+    unpacked_image(&pixels, width, height, format)
 }
 
-fn char_tile(ch: char) -> (i32, i32) {
-    match ch {
-        '0'..='9' => position(ch, '0', 0), 
-        'a'..='z' => position(ch, 'a', 1),
-        'A'..='Z' => position(ch, 'A', 4),
-        ':' => (6, 3),
-        '-' => (7, 3),
-        '?' => (8, 3),
-        '!' => (9, 3),
-        '.' => (6, 6),
-        ',' => (7, 6),
-        ' ' => (8, 6),
-        _ => (9, 6),
+// the surface is in whatever format the window prefers, so the frame is read back and written up in that format,
+// with the channels moved to and from RGBA for the filters. Each pixel is a packed 32 bit value, with red, green
+// and blue shifted into place and alpha too if the format has any
+fn channel_shifts(format: PixelFormatEnum) -> Result<([u32; 3], Option<u32>), EngineError> {
+    match format {
+        PixelFormatEnum::ARGB8888 => Ok(([16, 8, 0], Some(24))),
+        PixelFormatEnum::RGB888 => Ok(([16, 8, 0], None)),
+        PixelFormatEnum::ABGR8888 => Ok(([0, 8, 16], Some(24))),
+        PixelFormatEnum::BGR888 => Ok(([0, 8, 16], None)),
+        PixelFormatEnum::RGBA8888 => Ok(([24, 16, 8], Some(0))),
+        PixelFormatEnum::BGRA8888 => Ok(([8, 16, 24], Some(0))),
+        _ => Err(EngineError::Render(format!("can't read back a surface in {format:?}"))),
     }
 }
 
-fn position(ch: char, base: char, starting_row: i32) -> (i32, i32) {
-    let offset = ch as i32 - base as i32;
-    (offset % 10, (offset / 10) + starting_row)
+fn unpacked_image(pixels: &[u8], width: u32, height: u32, format: PixelFormatEnum) -> Result<RgbaImage, EngineError> {
+    let (shifts, alpha) = channel_shifts(format)?;
+    let rgba = pixels.chunks_exact(4).flat_map(|bytes| {
+        let packed = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let channel = |shift: u32| (packed >> shift) as u8;
+        [channel(shifts[0]), channel(shifts[1]), channel(shifts[2]), alpha.map(channel).unwrap_or(255)]
+    }).collect();
+    RgbaImage::from_raw(width, height, rgba)
+        .ok_or_else(|| EngineError::Render("surface read back at the wrong size".to_string()))
+}
+
+fn packed_pixels(image: &RgbaImage, format: PixelFormatEnum) -> Result<Vec<u8>, EngineError> {
+    let (shifts, alpha) = channel_shifts(format)?;
+    Ok(image.pixels().flat_map(|Rgba([r, g, b, a])| {
+        let packed = (*r as u32) << shifts[0] | (*g as u32) << shifts[1] | (*b as u32) << shifts[2]
+            | alpha.map(|shift| (*a as u32) << shift).unwrap_or(0);
+        packed.to_ne_bytes()
+    }).collect())
+}
+
+fn calculate_target_rect(canvas: &WindowCanvas, width: u32, height: u32, integer_scaling: bool) -> Result<Rect, EngineError> {
+    let output_size = canvas.output_size().map_err(EngineError::Render)?;
+    let (x, y, width, height) = target_rect(output_size, (width, height), integer_scaling);
+    Ok(Rect::new(x, y, width, height))
 }
 
 pub struct FpsCounter
@@ -232,4 +573,47 @@ impl FpsCounter {
 
         self.then = now;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn primaries() -> RgbaImage {
+        RgbaImage::from_fn(4, 1, |x, _| match x {
+            0 => Rgba([255, 0, 0, 255]),
+            1 => Rgba([0, 255, 0, 255]),
+            2 => Rgba([0, 0, 255, 128]),
+            _ => Rgba([10, 20, 30, 0]),
+        })
+    }
+
+    #[test]
+    fn should_pack_channels_where_the_format_puts_them() {
+        let pixels = packed_pixels(&primaries(), PixelFormatEnum::ARGB8888).unwrap();
+
+        assert_eq!(u32::from_ne_bytes([pixels[0], pixels[1], pixels[2], pixels[3]]), 0xffff0000);
+        assert_eq!(u32::from_ne_bytes([pixels[8], pixels[9], pixels[10], pixels[11]]), 0x800000ff);
+    }
+
+    #[test]
+    fn should_keep_the_frame_unchanged_through_the_filter_round_trip() {
+        for format in [PixelFormatEnum::ARGB8888, PixelFormatEnum::ABGR8888, PixelFormatEnum::RGBA8888, PixelFormatEnum::BGRA8888] {
+            let pixels = packed_pixels(&primaries(), format).unwrap();
+
+            // scanlines leave the first row alone, so a one row frame should come back exactly as it went up
+            let mut frame = unpacked_image(&pixels, 4, 1, format).unwrap();
+            post_process(&mut frame, &[PostFilter::Scanlines]);
+
+            assert_eq!(frame, primaries(), "{format:?}");
+            assert_eq!(packed_pixels(&frame, format).unwrap(), pixels, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn should_read_formats_without_alpha_as_opaque() {
+        let pixels = packed_pixels(&primaries(), PixelFormatEnum::RGB888).unwrap();
+        let frame = unpacked_image(&pixels, 4, 1, PixelFormatEnum::RGB888).unwrap();
+
+        assert_eq!(frame.get_pixel(2, 0), &Rgba([0, 0, 255, 255]));
+    }
+}
@@ -0,0 +1,700 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+use crate::error::EngineError;
+
+use super::backend::RenderBackend;
+use super::batch::{Clip, SpriteBatch};
+use super::camera::Camera;
+use super::capture::{Captured, FrameRecorder};
+use super::font::Font;
+use super::lighting::{light_map, PointLight};
+use super::palette::{swap_palette, Palette};
+use super::postprocess::{post_process, PostFilter};
+use super::renderer::Text;
+use super::sprite::Sprite;
+use super::transition::Transition;
+use super::viewport::Viewport;
+
+pub struct SoftwareSheet {
+    pub image: RgbaImage,
+    pub tile_width: u32,
+    pub tile_height: u32,
+}
+
+impl SoftwareSheet {
+    pub fn new(image: RgbaImage, tile_width: u32, tile_height: u32) -> Self {
+        SoftwareSheet { image, tile_width, tile_height }
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P, tile_width: u32, tile_height: u32) -> Result<Self, EngineError> {
+        let image = image::open(path).map_err(|err| EngineError::Asset(err.to_string()))?.to_rgba8();
+        Ok(SoftwareSheet::new(image, tile_width, tile_height))
+    }
+
+    pub fn recoloured(&self, from: &Palette, to: &Palette) -> Self {
+        SoftwareSheet::new(swap_palette(&self.image, from, to), self.tile_width, self.tile_height)
+    }
+
+    pub fn source_rect(&self, Sprite { x, y, width, height, .. }: &Sprite) -> (i32, i32, u32, u32) {
+        (x * self.tile_width as i32, y * self.tile_height as i32, width * self.tile_width, height * self.tile_height)
+    }
+}
+
+pub struct SoftwareRenderer {
+    sheets: HashMap<String, SoftwareSheet>,
+    batch: SpriteBatch,
+    surface: RgbaImage,
+    held: RgbaImage,
+    post_filters: Vec<PostFilter>,
+    recorder: FrameRecorder,
+}
+
+impl SoftwareRenderer {
+    pub fn new(sheets: HashMap<String, SoftwareSheet>, columns: u32, rows: u32, tile_width: u32, tile_height: u32) -> Self {
+        SoftwareRenderer {
+            sheets,
+            batch: SpriteBatch::new(tile_width, tile_height),
+            surface: RgbaImage::new(columns * tile_width, rows * tile_height),
+            held: RgbaImage::new(columns * tile_width, rows * tile_height),
+            post_filters: Vec::new(),
+            recorder: FrameRecorder::new(Duration::from_secs(5), Duration::from_millis(1000 / 15)).with_enabled(false),
+        }
+    }
+
+    pub fn with_post_filters(self, post_filters: Vec<PostFilter>) -> Self {
+        SoftwareRenderer { post_filters, ..self }
+    }
+
+    pub fn with_recording(self, recording: bool) -> Self {
+        SoftwareRenderer { recorder: self.recorder.with_enabled(recording), ..self }
+    }
+
+    pub fn frame(&self) -> &RgbaImage {
+        &self.surface
+    }
+
+    // there's no clock here, so every presented frame counts as one recording interval
+    fn record_frame(&mut self) {
+        let interval = self.recorder.interval();
+        if self.recorder.tick(interval) {
+            self.recorder.push(self.surface.clone());
+        }
+    }
+
+    // the batch is emptied even when drawing fails, so one bad sprite doesn't come back every frame
+    fn draw_batch(&mut self) -> Result<(), EngineError> {
+        let result = self.draw_queued();
+        self.batch.clear();
+        result
+    }
+
+    fn draw_queued(&mut self) -> Result<(), EngineError> {
+        let height = self.surface.height() as i32;
+        let lighting = self.batch.ambient_light()
+            .map(|ambient| light_map(self.surface.width(), self.surface.height(), ambient, self.batch.glows()));
+        let runs = self.batch.runs();
+        let lit = runs.iter().position(|run| !run[0].0.layer.is_lit()).unwrap_or(runs.len());
+        for (index, run) in runs.iter().enumerate() {
+            if index == lit {
+                light(&mut self.surface, &lighting);
+            }
+            let sheet_name = run[0].0.sheet_name();
+            let sheet = self.sheets.get(&sheet_name)
+                .ok_or_else(|| EngineError::Render(format!("no sprite sheet named {sheet_name}")))?;
+            for (sprite, (x, y), clip) in run.iter() {
+                let source = sheet.source_rect(sprite);
+                let corrected_y = (height - y) - source.3 as i32;
+                let clip = clip.map(|(left, bottom, width, clip_height)| (left, height - bottom - clip_height as i32, width, clip_height));
+                draw_sprite(&mut self.surface, &sheet.image, sprite, source, (*x, corrected_y), clip);
+            }
+        }
+        if lit == runs.len() {
+            light(&mut self.surface, &lighting);
+        }
+        for ((x, y, width, fill_height), (r, g, b, a)) in self.batch.fills() {
+            for row in height - y - *fill_height as i32..height - y {
+                for column in *x..x + *width as i32 {
+                    blend_pixel(&mut self.surface, column, row, Rgba([*r, *g, *b, *a]));
+                }
+            }
+        }
+        for ((x1, y1), (x2, y2), (r, g, b, a)) in self.batch.lines() {
+            draw_line(&mut self.surface, (*x1, height - 1 - y1), (*x2, height - 1 - y2), Rgba([*r, *g, *b, *a]));
+        }
+        Ok(())
+    }
+}
+
+impl RenderBackend for SoftwareRenderer {
+    fn set_camera(&mut self, camera: Option<&Camera>) {
+        self.batch.set_camera(camera);
+    }
+
+    fn set_viewport(&mut self, viewport: Option<&Viewport>) {
+        self.batch.set_viewport(viewport);
+    }
+
+    fn add_font(&mut self, name: &str, font: Font) {
+        self.batch.add_font(name, font);
+    }
+
+    fn draw_sprite(&mut self, sprite: &Sprite, x: f64, y: f64) {
+        self.batch.push_sprite(sprite, x, y);
+    }
+
+    fn draw_text(&mut self, text: &Text, x: f64, y: f64) {
+        self.batch.push_text(text, x, y);
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, colour: (u8, u8, u8, u8)) {
+        self.batch.push_fill(x, y, width, height, colour);
+    }
+
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), colour: (u8, u8, u8, u8)) {
+        self.batch.push_line(from, to, colour);
+    }
+
+    fn draw_circle(&mut self, center: (f64, f64), radius: f64, colour: (u8, u8, u8, u8)) {
+        self.batch.push_circle(center, radius, colour);
+    }
+
+    fn set_ambient_light(&mut self, ambient: Option<(u8, u8, u8)>) {
+        self.batch.set_ambient_light(ambient);
+    }
+
+    fn draw_light(&mut self, x: f64, y: f64, light: &PointLight) {
+        self.batch.push_light(x, y, light);
+    }
+
+    fn clear(&mut self) -> Result<(), EngineError> {
+        self.surface.pixels_mut().for_each(|pixel| *pixel = Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), EngineError> {
+        self.draw_batch()?;
+        post_process(&mut self.surface, &self.post_filters);
+        self.record_frame();
+        Ok(())
+    }
+
+    fn hold_frame(&mut self) -> Result<(), EngineError> {
+        self.draw_batch()?;
+        self.held = self.surface.clone();
+        self.clear()
+    }
+
+    fn present_transition(&mut self, transition: &Transition) -> Result<(), EngineError> {
+        self.draw_batch()?;
+        let mut frame = self.held.clone();
+        for (left, top, width, height) in transition.regions(frame.width(), frame.height()) {
+            for y in top..top + height as i32 {
+                for x in left..left + width as i32 {
+                    if let Some(colour) = pixel(&self.surface, x, y) {
+                        frame.put_pixel(x as u32, y as u32, colour);
+                    }
+                }
+            }
+        }
+        let shade = transition.shade();
+        if shade > 0 {
+            for y in 0..frame.height() as i32 {
+                for x in 0..frame.width() as i32 {
+                    blend_pixel(&mut frame, x, y, Rgba([0, 0, 0, shade]));
+                }
+            }
+        }
+        post_process(&mut frame, &self.post_filters);
+        self.surface = frame;
+        self.record_frame();
+        Ok(())
+    }
+}
+
+impl Captured for SoftwareRenderer {
+    fn screenshot(&mut self) -> Result<RgbaImage, EngineError> {
+        Ok(self.surface.clone())
+    }
+
+    fn recorder(&mut self) -> &mut FrameRecorder {
+        &mut self.recorder
+    }
+}
+
+// the clip is left, top, width and height, counted down from the top like everything else here
+fn draw_sprite(surface: &mut RgbaImage, sheet: &RgbaImage, sprite: &Sprite, (source_x, source_y, width, height): (i32, i32, u32, u32), (left, top): (i32, i32), clip: Option<Clip>) {
+    let ((scaled_left, scaled_top, scaled_width, scaled_height), (pivot_x, pivot_y)) = sprite.placement(left, top, width, height);
+    let (scale_x, scale_y) = sprite.scale;
+    if scale_x == 0.0 || scale_y == 0.0 {
+        return;
+    }
+    let screen_pivot = (scaled_left + pivot_x, scaled_top + pivot_y);
+    let local_pivot = (pivot_x / scale_x, pivot_y / scale_y);
+    let (sin, cos) = sprite.rotation.to_radians().sin_cos();
+
+    let corners = [(0.0, 0.0), (scaled_width, 0.0), (0.0, scaled_height), (scaled_width, scaled_height)]
+        .map(|(cx, cy)| {
+            let (dx, dy) = (cx - pivot_x, cy - pivot_y);
+            (screen_pivot.0 + dx * cos - dy * sin, screen_pivot.1 + dx * sin + dy * cos)
+        });
+    let min_x = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min).floor() as i32;
+    let max_x = corners.iter().map(|c| c.0).fold(f64::NEG_INFINITY, f64::max).ceil() as i32;
+    let min_y = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min).floor() as i32;
+    let max_y = corners.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max).ceil() as i32;
+    let (min_x, min_y, max_x, max_y) = match clip {
+        Some((clip_x, clip_y, clip_width, clip_height)) =>
+            (min_x.max(clip_x), min_y.max(clip_y), max_x.min(clip_x + clip_width as i32), max_y.min(clip_y + clip_height as i32)),
+        None => (min_x, min_y, max_x, max_y),
+    };
+
+    for target_y in min_y..max_y {
+        for target_x in min_x..max_x {
+            let (dx, dy) = (target_x as f64 + 0.5 - screen_pivot.0, target_y as f64 + 0.5 - screen_pivot.1);
+            let u = ((dx * cos + dy * sin) / scale_x + local_pivot.0).floor() as i32;
+            let v = ((dy * cos - dx * sin) / scale_y + local_pivot.1).floor() as i32;
+            if u < 0 || v < 0 || u >= width as i32 || v >= height as i32 {
+                continue;
+            }
+            let (sx, sy) = (
+                source_x + if sprite.flip_x { width as i32 - 1 - u } else { u },
+                source_y + if sprite.flip_y { height as i32 - 1 - v } else { v },
+            );
+            if let Some(colour) = pixel(sheet, sx, sy) {
+                blend_pixel(surface, target_x, target_y, tint(colour, sprite));
+            }
+        }
+    }
+}
+
+// bresenham's line, with both ends included
+fn draw_line(surface: &mut RgbaImage, (x1, y1): (i32, i32), (x2, y2): (i32, i32), colour: Rgba<u8>) {
+    let (dx, dy) = ((x2 - x1).abs(), -(y2 - y1).abs());
+    let (step_x, step_y) = ((x2 - x1).signum(), (y2 - y1).signum());
+    let (mut x, mut y, mut error) = (x1, y1, dx + dy);
+    loop {
+        blend_pixel(surface, x, y, colour);
+        if (x, y) == (x2, y2) {
+            return;
+        }
+        if 2 * error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if 2 * error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+fn light(surface: &mut RgbaImage, lighting: &Option<RgbImage>) {
+    if let Some(lighting) = lighting {
+        for (pixel, Rgb([lr, lg, lb])) in surface.pixels_mut().zip(lighting.pixels()) {
+            let Rgba([r, g, b, _]) = pixel;
+            *r = (*r as u32 * *lr as u32 / 255) as u8;
+            *g = (*g as u32 * *lg as u32 / 255) as u8;
+            *b = (*b as u32 * *lb as u32 / 255) as u8;
+        }
+    }
+}
+
+fn tint(Rgba([r, g, b, a]): Rgba<u8>, sprite: &Sprite) -> Rgba<u8> {
+    let (tr, tg, tb, _) = sprite.tint;
+    let multiply = |c: u8, t: u8| ((c as u32 * t as u32 + 127) / 255) as u8;
+    Rgba([multiply(r, tr), multiply(g, tg), multiply(b, tb), multiply(a, sprite.alpha())])
+}
+
+fn pixel(image: &RgbaImage, x: i32, y: i32) -> Option<Rgba<u8>> {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        None
+    } else {
+        Some(*image.get_pixel(x as u32, y as u32))
+    }
+}
+
+fn blend_pixel(image: &mut RgbaImage, x: i32, y: i32, Rgba([r, g, b, a]): Rgba<u8>) {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 || a == 0 {
+        return;
+    }
+    let Rgba([dr, dg, db, da]) = *image.get_pixel(x as u32, y as u32);
+    let alpha = a as f64 / 255.0;
+    let mix = |s: u8, d: u8| (s as f64 * alpha + d as f64 * (1.0 - alpha)).round() as u8;
+    let out_alpha = (a as f64 + da as f64 * (1.0 - alpha)).round() as u8;
+    image.put_pixel(x as u32, y as u32, Rgba([mix(r, dr), mix(g, dg), mix(b, db), out_alpha]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::golden::assert_matches_golden;
+    use crate::graphics::layer::Layer;
+    use crate::graphics::lighting::ambient_light;
+    use crate::graphics::palette::variant_name;
+    use crate::graphics::renderer::align;
+    use crate::graphics::transition::TransitionKind;
+    use crate::graphics::viewport::SplitLayout;
+    use crate::shapes::shape::shape::Shape;
+
+    fn asset_sheets() -> HashMap<String, SoftwareSheet> {
+        let graphics = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/graphics");
+        let mut sheets = HashMap::new();
+        sheets.insert("Sprites".to_string(), SoftwareSheet::open(graphics.join("spritesheet.png"), 12, 12).unwrap());
+        sheets.insert("Text".to_string(), SoftwareSheet::open(graphics.join("spritefont.png"), 8, 8).unwrap());
+        sheets
+    }
+
+    fn checker_sheet() -> HashMap<String, SoftwareSheet> {
+        let mut image = RgbaImage::new(4, 2);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            *pixel = match (x, y) {
+                (0, 0) => Rgba([255, 0, 0, 255]),
+                (1, 0) => Rgba([0, 255, 0, 255]),
+                (0, 1) => Rgba([0, 0, 255, 255]),
+                (1, 1) => Rgba([255, 255, 255, 255]),
+                _ => Rgba([255, 255, 0, 128]),
+            }
+        }
+        let mut sheets = HashMap::new();
+        sheets.insert("Checker".to_string(), SoftwareSheet::new(image, 2, 2));
+        sheets
+    }
+
+    #[test]
+    fn should_place_sprites_from_the_bottom_left() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 1.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+        assert_eq!(frame.get_pixel(2, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 1), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn should_flip_sprites() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::sprite(0, 0, Layer::Items, true, true, "Checker"), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn should_blend_in_layer_order() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::new(1, 0, Layer::Effects, "Checker"), 0.0, 0.0);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        assert_eq!(renderer.frame().get_pixel(0, 0), &Rgba([255, 128, 0, 255]));
+    }
+
+    #[test]
+    fn should_draw_relative_to_the_camera() {
+        let mut camera = Camera::new(2.0, 1.0).with_bounds(0.0, 0.0, 10.0, 1.0);
+        camera.center_on(5.0, 0.5);
+
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 1, 2, 2);
+        renderer.set_camera(Some(&camera));
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 5.0, 0.0);
+        renderer.present().unwrap();
+
+        assert_eq!(renderer.frame().get_pixel(2, 0), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn should_report_missing_sprite_sheets() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Missing"), 0.0, 0.0);
+
+        assert_eq!(renderer.present(), Err(EngineError::Render("no sprite sheet named Missing".to_string())));
+        assert_eq!(renderer.present(), Ok(()));
+    }
+
+    #[test]
+    fn should_rotate_sprites_clockwise_around_their_pivot() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker").rotated(90.0), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(frame.get_pixel(1, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn should_scale_sprites_around_their_pivot() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 2, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker").around(0.0, 0.0).scaled(2.0, 2.0), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 0), &Rgba([0, 255, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 3), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn should_apply_opacity_and_tint() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker").tinted(255, 0, 0, 255), 0.0, 0.0);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker").faded(0.5), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([255, 128, 128, 255]));
+        assert_eq!(frame.get_pixel(1, 0), &Rgba([0, 128, 0, 255]));
+    }
+
+    #[test]
+    fn should_lay_out_text_in_a_registered_font() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 3, 2, 2, 2);
+        renderer.add_font("Tiny", Font::new("Checker", 1, 2).with_char_map(&["ab"]).with_width("a", 2));
+        renderer.draw_text(&Text::new("ab\na", align::LEFT | align::BOTTOM).in_font("Tiny").coloured(3..4, 255, 0, 255), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([255, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(2, 0), &Rgba([128, 128, 0, 128]));
+        assert_eq!(frame.get_pixel(1, 3), &Rgba([255, 0, 255, 255]));
+        assert_eq!(frame.get_pixel(2, 3), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_animate_text_marked_up_with_effects() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 1, 2, 2);
+        renderer.add_font("Tiny", Font::new("Checker", 1, 2).with_char_map(&["ab"]));
+        renderer.draw_text(&Text::new("<type>ab</type>", align::LEFT | align::BOTTOM).in_font("Tiny").at_time(0.05), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(2, 0), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_composite_both_screens_during_a_transition() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 1, 2, 2);
+        let mut transition = Transition::new(TransitionKind::Wipe, Duration::from_secs(1));
+        transition.update(&Duration::from_millis(500));
+
+        renderer.clear().unwrap();
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 1.0, 0.0);
+        renderer.hold_frame().unwrap();
+        renderer.draw_sprite(&Sprite::sprite(0, 0, Layer::Items, true, true, "Checker"), 0.0, 0.0);
+        renderer.draw_sprite(&Sprite::sprite(0, 0, Layer::Items, true, true, "Checker"), 1.0, 0.0);
+        renderer.present_transition(&transition).unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(2, 0), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn should_filter_the_whole_transition_like_golden() {
+        let mut renderer = SoftwareRenderer::new(asset_sheets(), 8, 3, 12, 12)
+            .with_post_filters(vec![PostFilter::Bloom, PostFilter::ShadowMask, PostFilter::Scanlines]);
+        let mut transition = Transition::new(TransitionKind::Iris, Duration::from_secs(1));
+        transition.update(&Duration::from_millis(400));
+
+        renderer.clear().unwrap();
+        renderer.draw_text(&Text::new("OLD", align::CENTER | align::MIDDLE), 1.5, 1.5);
+        renderer.hold_frame().unwrap();
+        renderer.draw_sprite(&Sprite::new(0, 2, Layer::Actors, "Sprites"), 3.5, 1.0);
+        renderer.draw_sprite(&Sprite::new(0, 5, Layer::Actors, "Sprites"), 4.5, 1.0);
+        renderer.present_transition(&transition).unwrap();
+
+        assert_matches_golden(renderer.frame(), "filtered-transition");
+    }
+
+    #[test]
+    fn should_record_presented_frames() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2).with_recording(true);
+        renderer.present().unwrap();
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frames = renderer.recorder().frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(&renderer.screenshot().unwrap(), &frames[1]);
+    }
+
+    #[test]
+    fn should_draw_palette_variants() {
+        let mut sheets = checker_sheet();
+        let red = Palette::new(&[(255, 0, 0)]);
+        let variant = sheets["Checker"].recoloured(&red, &Palette::new(&[(0, 255, 255)]));
+        sheets.insert(variant_name("Checker", "Cyan"), variant);
+
+        let mut renderer = SoftwareRenderer::new(sheets, 2, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker").with_palette("Cyan"), 1.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(2, 0), &Rgba([0, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(3, 1), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn should_draw_lines_over_sprites() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 2, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.draw_line((0.0, 0.0), (1.5, 1.5), (0, 255, 255, 255));
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 3), &Rgba([0, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(1, 2), &Rgba([0, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(3, 0), &Rgba([0, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(0, 2), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 3), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_light_everything_but_the_interface() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 4, 1, 2, 2);
+        renderer.set_ambient_light(Some((0, 0, 0)));
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Ui, "Checker"), 2.0, 0.0);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 3.0, 0.0);
+        renderer.draw_light(0.5, 0.5, &PointLight::new(1.0, (255, 255, 255)));
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        let Rgba([r, g, b, _]) = *frame.get_pixel(1, 1);
+        assert!(r > 0 && r < 255 && r == g && g == b);
+        assert_eq!(frame.get_pixel(5, 1), &Rgba([255, 255, 255, 255]));
+        assert_eq!(frame.get_pixel(7, 1), &Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn should_light_a_dark_level_like_golden() {
+        let mut renderer = SoftwareRenderer::new(asset_sheets(), 8, 3, 12, 12);
+        renderer.clear().unwrap();
+        renderer.set_ambient_light(Some(ambient_light(0.85)));
+        for x in 0..8 {
+            renderer.draw_sprite(&Sprite::new(0, 2, Layer::Items, "Sprites"), x as f64, 0.0);
+        }
+        renderer.draw_sprite(&Sprite::new(0, 1, Layer::Actors, "Sprites"), 2.0, 1.0);
+        renderer.draw_sprite(&Sprite::new(0, 5, Layer::Actors, "Sprites"), 6.0, 1.0);
+        renderer.draw_light(2.5, 1.5, &PointLight::new(2.5, (255, 224, 160)));
+        renderer.draw_text(&Text::new("DARK", align::LEFT | align::TOP), 0.0, 3.0);
+        renderer.present().unwrap();
+
+        assert_matches_golden(renderer.frame(), "dark-level");
+    }
+
+    #[test]
+    fn should_leave_the_picture_alone_without_lighting() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2);
+        renderer.set_ambient_light(Some((0, 0, 0)));
+        renderer.present().unwrap();
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        assert_eq!(renderer.frame().get_pixel(1, 1), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn should_post_process_presented_frames() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 1, 1, 2, 2).with_post_filters(vec![PostFilter::Scanlines]);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.0, 0.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([170, 170, 170, 255]));
+    }
+
+    #[test]
+    fn should_keep_split_views_apart_under_a_shared_hud() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 4, 2, 2, 2);
+        let views: Vec<(Viewport, Camera)> = Viewport::split(4.0, 2.0, 2, SplitLayout::Horizontal, 0.0).into_iter()
+            .zip([Camera::new(2.0, 2.0), Camera::new(2.0, 2.0).shifted(10.0, 0.0)])
+            .collect();
+        renderer.draw_views(&views, |renderer, _| {
+            renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 1.5, 0.0);
+            renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 10.0, 0.0);
+        });
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Ui, "Checker"), 1.5, 1.0);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(3, 3), &Rgba([0, 0, 255, 255]));
+        assert_eq!(frame.get_pixel(4, 3), &Rgba([0, 0, 255, 255]));
+        assert_eq!(frame.get_pixel(4, 2), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(4, 0), &Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn should_fill_rectangles_over_sprites() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 1, 2, 2);
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Ui, "Checker"), 0.0, 0.0);
+        renderer.fill_rect(0.5, 0.0, 1.0, 0.5, (0, 0, 0, 255));
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 1), &Rgba([0, 0, 255, 255]));
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([0, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(2, 1), &Rgba([0, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(1, 0), &Rgba([0, 255, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 1), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_zoom_sprites_with_the_camera() {
+        let camera = Camera::new(2.0, 2.0).with_zoom(2.0);
+
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 2, 2, 2, 2);
+        renderer.set_camera(Some(&camera));
+        renderer.draw_sprite(&Sprite::new(0, 0, Layer::Items, "Checker"), 0.5, 0.5);
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(1, 1), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 0), &Rgba([0, 255, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 3), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn should_outline_shapes() {
+        let mut renderer = SoftwareRenderer::new(checker_sheet(), 4, 4, 2, 2);
+        renderer.draw_shape(&Shape::bbox(0.0, 0.0, 1.5, 1.5), (255, 0, 0, 255));
+        renderer.draw_shape(&Shape::circle((2.5, 2.5), 1.0), (0, 255, 0, 255));
+        renderer.present().unwrap();
+
+        let frame = renderer.frame();
+        assert_eq!(frame.get_pixel(0, 7), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(3, 4), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(1, 6), &Rgba([0, 0, 0, 0]));
+        assert_eq!(frame.get_pixel(7, 2), &Rgba([0, 255, 0, 255]));
+        assert_eq!(frame.get_pixel(5, 0), &Rgba([0, 255, 0, 255]));
+        assert_eq!(frame.get_pixel(5, 2), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn should_render_title_text_like_golden() {
+        let mut renderer = SoftwareRenderer::new(asset_sheets(), 30, 20, 12, 12);
+        renderer.clear().unwrap();
+        renderer.draw_text(&Text::new("PANDAMONIUM", align::CENTER | align::MIDDLE), 13.0, 10.0);
+        renderer.draw_text(&Text::new("1: play as Blue", align::CENTER | align::MIDDLE), 13.0, 5.0);
+        renderer.draw_sprite(&Sprite::new(0, 1, Layer::Actors, "Sprites"), 12.5, 7.0);
+        renderer.draw_sprite(&Sprite::sprite(0, 4, Layer::Actors, true, false, "Sprites"), 13.5, 7.0);
+        renderer.present().unwrap();
+
+        assert_matches_golden(renderer.frame(), "title");
+    }
+}
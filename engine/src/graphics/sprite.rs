@@ -1,46 +1,179 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use image::RgbaImage;
+use sdl2::pixels::PixelFormatEnum;
 use sdl2::rect::Rect;
-use sdl2::render::Texture;
+use sdl2::render::{BlendMode, Texture, TextureCreator};
+use sdl2::video::WindowContext;
 
 use component_derive::Variable;
 
+use crate::error::EngineError;
+
+use super::atlas::Atlas;
+use super::layer::Layer;
+use super::palette::variant_name;
+
 #[derive(Clone, Variable)]
 pub struct Sprite {
     pub tileset: String,
     pub x: i32,
     pub y: i32,
-    pub z: f64,
+    pub layer: Layer,
+    pub depth: f64,
     pub width: u32,
     pub height: u32,
     pub flip_x: bool,
     pub flip_y: bool,
+    pub rotation: f64,
+    pub pivot: (f64, f64),
+    pub scale: (f64, f64),
+    pub opacity: f64,
+    pub tint: (u8, u8, u8, u8),
+    pub palette: Option<String>,
 }
 
 impl Sprite {
-    pub fn new(x: i32, y: i32, z: f64, tileset: &str) -> Sprite {
-        Sprite { tileset: tileset.to_string(), x, y, z, flip_x: false, flip_y: false, width: 1, height: 1 }
+    pub fn new(x: i32, y: i32, layer: Layer, tileset: &str) -> Sprite {
+        Sprite::sprite(x, y, layer, false, false, tileset)
+    }
+
+    pub fn multi(x: i32, y: i32, layer: Layer, width: u32, height: u32, tileset: &str) -> Sprite {
+        Sprite { width, height, ..Sprite::new(x, y, layer, tileset) }
+    }
+
+    pub fn sprite(x: i32, y: i32, layer: Layer, flip_x: bool, flip_y: bool, tileset: &str) -> Sprite {
+        Sprite {
+            tileset: tileset.to_string(),
+            x,
+            y,
+            layer,
+            depth: 0.0,
+            flip_x,
+            flip_y,
+            width: 1,
+            height: 1,
+            rotation: 0.0,
+            pivot: (0.5, 0.5),
+            scale: (1.0, 1.0),
+            opacity: 1.0,
+            tint: (255, 255, 255, 255),
+            palette: None,
+        }
+    }
+
+    // only needed where sprites in the same layer overlap; higher depths are drawn on top
+    pub fn with_depth(self, depth: f64) -> Sprite {
+        Sprite { depth, ..self }
+    }
+
+    // rotation is in degrees clockwise, around a pivot given as a fraction of the sprite from its bottom left
+    pub fn rotated(self, rotation: f64) -> Sprite {
+        Sprite { rotation, ..self }
+    }
+
+    pub fn around(self, pivot_x: f64, pivot_y: f64) -> Sprite {
+        Sprite { pivot: (pivot_x, pivot_y), ..self }
+    }
+
+    pub fn scaled(self, scale_x: f64, scale_y: f64) -> Sprite {
+        Sprite { scale: (scale_x.max(0.0), scale_y.max(0.0)), ..self }
+    }
+
+    pub fn faded(self, opacity: f64) -> Sprite {
+        Sprite { opacity: opacity.clamp(0.0, 1.0), ..self }
+    }
+
+    pub fn tinted(self, r: u8, g: u8, b: u8, a: u8) -> Sprite {
+        Sprite { tint: (r, g, b, a), ..self }
+    }
+
+    pub fn with_palette(self, palette: &str) -> Sprite {
+        Sprite { palette: Some(palette.to_string()), ..self }
+    }
+
+    pub fn sheet_name(&self) -> String {
+        match &self.palette {
+            Some(palette) => variant_name(&self.tileset, palette),
+            None => self.tileset.clone(),
+        }
+    }
+
+    pub fn same_sheet(&self, other: &Sprite) -> bool {
+        self.tileset == other.tileset && self.palette == other.palette
+    }
+
+    pub fn alpha(&self) -> u8 {
+        (self.opacity * self.tint.3 as f64).round() as u8
     }
 
-    pub fn multi(x: i32, y: i32, z: f64, width: u32, height: u32, tileset: &str) -> Sprite {
-        Sprite { tileset: tileset.to_string(), x, y, z, width, height, flip_x: false, flip_y: false }
+    pub fn is_transformed(&self) -> bool {
+        self.rotation != 0.0 || self.scale != (1.0, 1.0)
     }
 
-    pub fn sprite(x: i32, y: i32, z: f64, flip_x: bool, flip_y: bool, tileset: &str) -> Sprite {
-        Sprite { tileset: tileset.to_string(), x, y, z, flip_x, flip_y, width: 1, height: 1 }
+    // takes the unscaled screen rectangle (y down) and gives the scaled one, plus the pivot relative to it
+    pub fn placement(&self, left: i32, top: i32, width: u32, height: u32) -> ((f64, f64, f64, f64), (f64, f64)) {
+        let (pivot_x, pivot_y) = self.pivot;
+        let (scale_x, scale_y) = self.scale;
+        let (width, height) = (width as f64, height as f64);
+        let pivot = (pivot_x * width * scale_x, (1.0 - pivot_y) * height * scale_y);
+        let screen_pivot = (left as f64 + pivot_x * width, top as f64 + (1.0 - pivot_y) * height);
+        ((screen_pivot.0 - pivot.0, screen_pivot.1 - pivot.1, width * scale_x, height * scale_y), pivot)
     }
 }
 
+// sheets packed into an atlas share its texture, each knowing where on it its own cells start
 pub struct SpriteSheet<'a> {
-    pub spritesheet: Texture<'a>,
+    pub spritesheet: Rc<RefCell<Texture<'a>>>,
+    pub origin: (i32, i32),
     pub tile_width: u32,
     pub tile_height: u32,
 }
 
 impl <'a> SpriteSheet<'a> {
     pub fn new(spritesheet: Texture<'a>, tile_width: u32, tile_height: u32) -> Self {
-        SpriteSheet { spritesheet, tile_width, tile_height }
+        SpriteSheet::in_atlas(&Rc::new(RefCell::new(spritesheet)), (0, 0), tile_width, tile_height)
+    }
+
+    pub fn in_atlas(atlas: &Rc<RefCell<Texture<'a>>>, origin: (i32, i32), tile_width: u32, tile_height: u32) -> Self {
+        SpriteSheet { spritesheet: atlas.clone(), origin, tile_width, tile_height }
+    }
+
+    pub fn from_image(texture_creator: &'a TextureCreator<WindowContext>, image: &RgbaImage, tile_width: u32, tile_height: u32) -> Result<Self, EngineError> {
+        Ok(SpriteSheet::new(texture_from_image(texture_creator, image)?, tile_width, tile_height))
+    }
+
+    // packs the sheets, given with their cell sizes, into as few textures as will hold them
+    pub fn atlas(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        sheets: HashMap<String, (RgbaImage, (u32, u32))>,
+        page_size: u32,
+    ) -> Result<HashMap<String, Self>, EngineError> {
+        let cells: HashMap<String, (u32, u32)> = sheets.iter().map(|(name, (_, cell))| (name.clone(), *cell)).collect();
+        let Atlas { pages, placements } = Atlas::pack(sheets.into_iter().map(|(name, (image, _))| (name, image)).collect(), page_size);
+        let textures = pages.iter()
+            .map(|page| texture_from_image(texture_creator, page).map(|texture| Rc::new(RefCell::new(texture))))
+            .collect::<Result<Vec<_>, EngineError>>()?;
+        Ok(placements.into_iter().map(|(name, (page, origin))| {
+            let (tile_width, tile_height) = cells[&name];
+            let sheet = SpriteSheet::in_atlas(&textures[page], origin, tile_width, tile_height);
+            (name, sheet)
+        }).collect())
     }
 
     pub fn source_rect(&self, Sprite{ x, y, width, height, .. }: &Sprite) -> Rect {
-        Rect::new(x * self.tile_width as i32, y * self.tile_height as i32, width * self.tile_width, height * self.tile_height)
+        let (left, top) = self.origin;
+        Rect::new(left + x * self.tile_width as i32, top + y * self.tile_height as i32, width * self.tile_width, height * self.tile_height)
     }
-}
\ No newline at end of file
+}
+
+pub fn texture_from_image<'a>(texture_creator: &'a TextureCreator<WindowContext>, image: &RgbaImage) -> Result<Texture<'a>, EngineError> {
+    let mut texture = texture_creator.create_texture_static(PixelFormatEnum::ABGR8888, image.width(), image.height())
+        .map_err(|err| EngineError::Asset(err.to_string()))?;
+    texture.update(None, image.as_raw(), image.width() as usize * 4)
+        .map_err(|err| EngineError::Asset(err.to_string()))?;
+    texture.set_blend_mode(BlendMode::Blend);
+    Ok(texture)
+}
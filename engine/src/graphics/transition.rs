@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use crate::error::EngineError;
+use crate::game_loop::GameLoop;
+
+use super::backend::RenderBackend;
+
+const DISSOLVE_CELL: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionKind {
+    Fade,
+    Wipe,
+    Dissolve,
+    Iris,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    duration: f64,
+    elapsed: f64,
+}
+
+impl Transition {
+    pub fn new(kind: TransitionKind, duration: Duration) -> Self {
+        Transition { kind, duration: duration.as_secs_f64(), elapsed: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: &Duration) {
+        self.elapsed = (self.elapsed + dt.as_secs_f64()).min(self.duration);
+    }
+
+    pub fn progress(&self) -> f64 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            self.elapsed / self.duration
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    // the parts of the frame (in pixels, from the top left) where the incoming screen shows through
+    pub fn regions(&self, width: u32, height: u32) -> Vec<(i32, i32, u32, u32)> {
+        let progress = self.progress();
+        match self.kind {
+            TransitionKind::Fade if progress < 0.5 => Vec::new(),
+            TransitionKind::Fade => vec![(0, 0, width, height)],
+            TransitionKind::Wipe => match (width as f64 * progress).round() as u32 {
+                0 => Vec::new(),
+                wiped => vec![(0, 0, wiped, height)],
+            },
+            TransitionKind::Dissolve => dissolve(width, height, progress),
+            TransitionKind::Iris => iris(width, height, progress),
+        }
+    }
+
+    // how dark to draw over the whole frame, fading out to black and back in again
+    pub fn shade(&self) -> u8 {
+        match self.kind {
+            TransitionKind::Fade => (255.0 * (1.0 - (2.0 * self.progress() - 1.0).abs())).round() as u8,
+            _ => 0,
+        }
+    }
+
+    pub fn render<'a, R, A, B>(&self, renderer: &mut R, outgoing: &A, incoming: &B) -> Result<(), EngineError>
+    where R: RenderBackend, A: GameLoop<'a, R>, B: GameLoop<'a, R>
+    {
+        renderer.clear()?;
+        outgoing.render(renderer)?;
+        renderer.hold_frame()?;
+        incoming.render(renderer)?;
+        renderer.present_transition(self)
+    }
+}
+
+fn dissolve(width: u32, height: u32, progress: f64) -> Vec<(i32, i32, u32, u32)> {
+    let mut regions = Vec::new();
+    for y in (0..height).step_by(DISSOLVE_CELL as usize) {
+        for x in (0..width).step_by(DISSOLVE_CELL as usize) {
+            if threshold(x / DISSOLVE_CELL, y / DISSOLVE_CELL) < progress {
+                regions.push((x as i32, y as i32, DISSOLVE_CELL.min(width - x), DISSOLVE_CELL.min(height - y)));
+            }
+        }
+    }
+    regions
+}
+
+// a cheap hash, so each cell dissolves at the same point on every frame
+fn threshold(x: u32, y: u32) -> f64 {
+    let mut hash = x.wrapping_mul(0x9E37_79B1) ^ y.wrapping_mul(0x85EB_CA77);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+    (hash % 1000) as f64 / 1000.0
+}
+
+fn iris(width: u32, height: u32, progress: f64) -> Vec<(i32, i32, u32, u32)> {
+    let (center_x, center_y) = (width as f64 / 2.0, height as f64 / 2.0);
+    let radius = progress * (center_x * center_x + center_y * center_y).sqrt();
+    let mut regions = Vec::new();
+    for y in 0..height {
+        let dy = y as f64 + 0.5 - center_y;
+        if dy.abs() >= radius {
+            continue;
+        }
+        let half_width = (radius * radius - dy * dy).sqrt();
+        let left = (center_x - half_width).round().max(0.0) as i32;
+        let right = (center_x + half_width).round().min(width as f64) as i32;
+        if right > left {
+            regions.push((left, y as i32, (right - left) as u32, 1));
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(kind: TransitionKind, progress: f64) -> Transition {
+        let mut transition = Transition::new(kind, Duration::from_secs(1));
+        transition.update(&Duration::from_secs_f64(progress));
+        transition
+    }
+
+    fn area(regions: &[(i32, i32, u32, u32)]) -> u32 {
+        regions.iter().map(|(_, _, w, h)| w * h).sum()
+    }
+
+    #[test]
+    fn should_finish_after_its_duration() {
+        let mut transition = at(TransitionKind::Wipe, 0.75);
+        assert!(!transition.is_finished());
+
+        transition.update(&Duration::from_millis(500));
+        assert!(transition.is_finished());
+        assert_eq!(transition.progress(), 1.0);
+    }
+
+    #[test]
+    fn should_fade_through_black() {
+        assert_eq!(at(TransitionKind::Fade, 0.25).shade(), 128);
+        assert_eq!(at(TransitionKind::Fade, 0.25).regions(10, 10), vec![]);
+        assert_eq!(at(TransitionKind::Fade, 0.5).shade(), 255);
+        assert_eq!(at(TransitionKind::Fade, 0.75).regions(10, 10), vec![(0, 0, 10, 10)]);
+        assert_eq!(at(TransitionKind::Fade, 1.0).shade(), 0);
+    }
+
+    #[test]
+    fn should_wipe_from_the_left() {
+        assert_eq!(at(TransitionKind::Wipe, 0.0).regions(10, 4), vec![]);
+        assert_eq!(at(TransitionKind::Wipe, 0.3).regions(10, 4), vec![(0, 0, 3, 4)]);
+    }
+
+    #[test]
+    fn should_open_the_iris_from_the_center() {
+        assert_eq!(area(&at(TransitionKind::Iris, 0.0).regions(20, 10)), 0);
+        assert_eq!(at(TransitionKind::Iris, 0.2).regions(20, 10), vec![(8, 3, 4, 1), (8, 4, 4, 1), (8, 5, 4, 1), (8, 6, 4, 1)]);
+        assert_eq!(area(&at(TransitionKind::Iris, 1.0).regions(20, 10)), 200);
+    }
+
+    #[test]
+    fn should_dissolve_more_cells_as_it_progresses() {
+        let (width, height) = (360, 240);
+        let half = area(&at(TransitionKind::Dissolve, 0.5).regions(width, height));
+
+        assert!(half > width * height * 2 / 5 && half < width * height * 3 / 5);
+        assert_eq!(area(&at(TransitionKind::Dissolve, 1.0).regions(width, height)), width * height);
+    }
+}
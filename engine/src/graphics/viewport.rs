@@ -0,0 +1,74 @@
+use super::camera::Camera;
+
+// horizontal puts the views side by side, vertical stacks them from the top down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitLayout {
+    Horizontal,
+    Vertical,
+}
+
+// a part of the picture for one camera to draw into, in tiles up from the bottom left;
+// nothing drawn through it spills out of it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Viewport {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Viewport { x, y, width, height }
+    }
+
+    // shares the picture out equally, leaving a gap between neighbours
+    pub fn split(columns: f64, rows: f64, count: usize, layout: SplitLayout, gap: f64) -> Vec<Viewport> {
+        let count = count.max(1);
+        let gaps = gap * (count - 1) as f64;
+        (0..count).map(|index| match layout {
+            SplitLayout::Horizontal => {
+                let width = (columns - gaps) / count as f64;
+                Viewport::new(index as f64 * (width + gap), 0.0, width, rows)
+            }
+            SplitLayout::Vertical => {
+                let height = (rows - gaps) / count as f64;
+                Viewport::new(0.0, rows - height - index as f64 * (height + gap), columns, height)
+            }
+        }).collect()
+    }
+
+    // a camera that fills the view, looking at the same place as the one given
+    pub fn fit(&self, camera: &Camera) -> Camera {
+        camera.clone().resized(self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_split_side_by_side() {
+        assert_eq!(Viewport::split(30.0, 20.0, 2, SplitLayout::Horizontal, 0.0), vec![
+            Viewport::new(0.0, 0.0, 15.0, 20.0),
+            Viewport::new(15.0, 0.0, 15.0, 20.0),
+        ]);
+    }
+
+    #[test]
+    fn should_stack_from_the_top_with_gaps() {
+        assert_eq!(Viewport::split(30.0, 21.0, 2, SplitLayout::Vertical, 1.0), vec![
+            Viewport::new(0.0, 11.0, 30.0, 10.0),
+            Viewport::new(0.0, 0.0, 30.0, 10.0),
+        ]);
+    }
+
+    #[test]
+    fn should_fit_a_camera_to_the_view() {
+        let camera = Viewport::new(0.0, 0.0, 10.0, 4.0).fit(&Camera::new(20.0, 10.0));
+
+        assert_eq!((camera.width, camera.height), (10.0, 4.0));
+        assert_eq!((camera.x + 5.0, camera.y + 2.0), (10.0, 5.0));
+    }
+}
@@ -1,7 +1,11 @@
 pub mod audio;
+pub mod error;
 pub mod events;
 pub mod game_loop;
 pub mod graphics;
 pub mod shapes;
 pub mod map;
+pub mod particles;
+pub mod profiler;
 pub mod tools;
+pub mod ui;
@@ -0,0 +1,260 @@
+use std::time::Duration;
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use component_derive::Variable;
+
+use crate::graphics::sprite::Sprite;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Particle {
+    x: f64,
+    y: f64,
+    dx: f64,
+    dy: f64,
+    age: f64,
+    lifetime: f64,
+    alive: bool,
+}
+
+// particles live inside the emitter and are reused once they die, rather than each being an entity
+#[derive(Clone, Variable)]
+pub struct Emitter {
+    pub rate: f64,
+    pub burst: u32,
+    pub lifetime: (f64, f64),
+    pub speed: (f64, f64),
+    pub direction: f64,
+    pub spread: f64,
+    pub gravity: f64,
+    pub frames: Vec<Sprite>,
+    pub duration: Option<f64>,
+    particles: Vec<Particle>,
+    elapsed: f64,
+    owed: f64,
+    burst_pending: bool,
+    rng: SmallRng,
+}
+
+impl Emitter {
+    pub fn new(frames: Vec<Sprite>) -> Self {
+        Emitter {
+            rate: 0.0,
+            burst: 0,
+            lifetime: (1.0, 1.0),
+            speed: (0.0, 0.0),
+            direction: 90.0,
+            spread: 0.0,
+            gravity: 0.0,
+            frames,
+            duration: None,
+            particles: Vec::new(),
+            elapsed: 0.0,
+            owed: 0.0,
+            burst_pending: true,
+            rng: SmallRng::from_entropy(),
+        }
+    }
+
+    pub fn with_rate(self, rate: f64) -> Self {
+        Emitter { rate, ..self }
+    }
+
+    pub fn with_burst(self, burst: u32) -> Self {
+        Emitter { burst, ..self }
+    }
+
+    pub fn with_lifetime(self, min: f64, max: f64) -> Self {
+        Emitter { lifetime: (min, max), ..self }
+    }
+
+    pub fn with_speed(self, min: f64, max: f64) -> Self {
+        Emitter { speed: (min, max), ..self }
+    }
+
+    // direction is in degrees anticlockwise from the right, so 90 is straight up; spread is the width of the cone
+    pub fn with_cone(self, direction: f64, spread: f64) -> Self {
+        Emitter { direction, spread, ..self }
+    }
+
+    pub fn with_gravity(self, gravity: f64) -> Self {
+        Emitter { gravity, ..self }
+    }
+
+    pub fn with_duration(self, duration: f64) -> Self {
+        Emitter { duration: Some(duration), ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Emitter { rng: SmallRng::seed_from_u64(seed), ..self }
+    }
+
+    pub fn update(&mut self, dt: &Duration, x: f64, y: f64) {
+        let dt = dt.as_secs_f64();
+        for particle in self.particles.iter_mut().filter(|particle| particle.alive) {
+            particle.age += dt;
+            if particle.age >= particle.lifetime {
+                particle.alive = false;
+                continue;
+            }
+            particle.dy -= self.gravity * dt;
+            particle.x += particle.dx * dt;
+            particle.y += particle.dy * dt;
+        }
+
+        if self.burst_pending {
+            self.burst_pending = false;
+            for _ in 0..self.burst {
+                self.emit(x, y);
+            }
+        }
+
+        if self.is_emitting() {
+            self.owed += self.rate * dt;
+            while self.owed >= 1.0 {
+                self.owed -= 1.0;
+                self.emit(x, y);
+            }
+        }
+        self.elapsed += dt;
+    }
+
+    pub fn live_count(&self) -> usize {
+        self.particles.iter().filter(|particle| particle.alive).count()
+    }
+
+    pub fn pool_size(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.burst_pending && !self.is_emitting() && self.live_count() == 0
+    }
+
+    // frames are spread evenly over each particle's lifetime
+    pub fn sprites(&self) -> Vec<(f64, f64, &Sprite)> {
+        if self.frames.is_empty() {
+            return Vec::new();
+        }
+        self.particles.iter()
+            .filter(|particle| particle.alive)
+            .map(|particle| {
+                let frame = ((particle.age / particle.lifetime) * self.frames.len() as f64) as usize;
+                (particle.x, particle.y, &self.frames[frame.min(self.frames.len() - 1)])
+            })
+            .collect()
+    }
+
+    fn is_emitting(&self) -> bool {
+        self.duration.map_or(true, |duration| self.elapsed < duration)
+    }
+
+    fn emit(&mut self, x: f64, y: f64) {
+        let lifetime = self.between(self.lifetime);
+        let speed = self.between(self.speed);
+        let offset = if self.spread > 0.0 { self.rng.gen_range(-0.5..0.5) * self.spread } else { 0.0 };
+        let (sin, cos) = (self.direction + offset).to_radians().sin_cos();
+        let particle = Particle { x, y, dx: speed * cos, dy: speed * sin, age: 0.0, lifetime, alive: true };
+        match self.particles.iter_mut().find(|particle| !particle.alive) {
+            Some(slot) => *slot = particle,
+            None => self.particles.push(particle),
+        }
+    }
+
+    fn between(&mut self, (min, max): (f64, f64)) -> f64 {
+        if max > min {
+            self.rng.gen_range(min..max)
+        } else {
+            min
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::layer::Layer;
+
+    fn emitter() -> Emitter {
+        Emitter::new(vec![Sprite::new(0, 0, Layer::Effects, "Sprites"), Sprite::new(1, 0, Layer::Effects, "Sprites")]).with_seed(7)
+    }
+
+    fn step(emitter: &mut Emitter, seconds: f64) {
+        emitter.update(&Duration::from_secs_f64(seconds), 0.0, 0.0);
+    }
+
+    #[test]
+    fn should_emit_a_burst_on_the_first_update() {
+        let mut emitter = emitter().with_burst(5).with_duration(0.0);
+        step(&mut emitter, 0.1);
+        step(&mut emitter, 0.1);
+
+        assert_eq!(emitter.live_count(), 5);
+    }
+
+    #[test]
+    fn should_emit_continuously_at_its_rate() {
+        let mut emitter = emitter().with_rate(10.0).with_lifetime(5.0, 5.0);
+        for _ in 0..10 {
+            step(&mut emitter, 0.05);
+        }
+
+        assert_eq!(emitter.live_count(), 5);
+    }
+
+    #[test]
+    fn should_reuse_dead_particles() {
+        let mut emitter = emitter().with_rate(10.0).with_lifetime(0.25, 0.25);
+        for _ in 0..40 {
+            step(&mut emitter, 0.05);
+        }
+
+        assert!(emitter.pool_size() <= 3);
+    }
+
+    #[test]
+    fn should_finish_once_particles_have_died() {
+        let mut emitter = emitter().with_burst(3).with_lifetime(0.5, 1.0).with_duration(0.0);
+        step(&mut emitter, 0.1);
+        assert!(!emitter.is_finished());
+
+        step(&mut emitter, 1.0);
+        assert!(emitter.is_finished());
+    }
+
+    #[test]
+    fn should_move_along_the_cone_and_fall_with_gravity() {
+        let mut emitter = emitter().with_burst(1).with_speed(2.0, 2.0).with_cone(0.0, 0.0).with_gravity(4.0).with_duration(0.0);
+        step(&mut emitter, 0.0);
+        step(&mut emitter, 0.5);
+
+        let sprites = emitter.sprites();
+        let (x, y, _) = sprites[0];
+        assert!((x - 1.0).abs() < 1e-9);
+        assert!((y + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_keep_within_the_cone() {
+        let mut emitter = emitter().with_burst(50).with_speed(1.0, 1.0).with_cone(90.0, 60.0).with_duration(0.0);
+        step(&mut emitter, 0.0);
+        step(&mut emitter, 1.0 / 2.0);
+
+        for (x, y, _) in emitter.sprites() {
+            assert!(y > 0.0);
+            assert!(x.abs() <= y * 30f64.to_radians().tan() + 1e-9);
+        }
+    }
+
+    #[test]
+    fn should_animate_over_each_particles_lifetime() {
+        let mut emitter = emitter().with_burst(1).with_lifetime(1.0, 1.0).with_duration(0.0);
+        step(&mut emitter, 0.0);
+        step(&mut emitter, 0.25);
+        assert_eq!(emitter.sprites()[0].2.x, 0);
+
+        step(&mut emitter, 0.5);
+        assert_eq!(emitter.sprites()[0].2.x, 1);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use component_derive::Event;
+
+use crate::events::EventTrait;
+use crate as engine;
+
+#[derive(Event)]
+pub struct StageTimed(pub &'static str, pub Duration);
+
+#[derive(Event)]
+pub struct ToggleProfilerOverlay;
+
+#[derive(Event)]
+pub struct DumpProfile(pub String);
+
+pub trait Profiled {
+    fn profiler(&mut self) -> &mut Profiler;
+}
+
+struct Stage {
+    name: &'static str,
+    samples: VecDeque<(u64, f64)>,
+}
+
+pub struct Profiler {
+    window: usize,
+    frame: u64,
+    stages: Vec<Stage>,
+    pub overlay: bool,
+}
+
+impl Profiler {
+    pub fn new(window: usize) -> Self {
+        Profiler { window, frame: 0, stages: Vec::new(), overlay: false }
+    }
+
+    pub fn record(&mut self, name: &'static str, duration: Duration) {
+        let index = match self.stages.iter().position(|stage| stage.name == name) {
+            Some(index) => index,
+            None => {
+                self.stages.push(Stage { name, samples: VecDeque::with_capacity(self.window) });
+                self.stages.len() - 1
+            }
+        };
+        let samples = &mut self.stages[index].samples;
+        if samples.len() == self.window {
+            samples.pop_front();
+        }
+        samples.push_back((self.frame, duration.as_secs_f64() * 1000.0));
+    }
+
+    pub fn end_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    pub fn stages(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|stage| stage.name).collect()
+    }
+
+    pub fn average(&self, name: &str) -> Option<f64> {
+        let samples = self.samples(name)?;
+        Some(samples.iter().sum::<f64>() / samples.len() as f64)
+    }
+
+    pub fn percentile(&self, name: &str, percentile: f64) -> Option<f64> {
+        let mut samples = self.samples(name)?;
+        samples.sort_by(f64::total_cmp);
+        let rank = ((percentile / 100.0) * samples.len() as f64).ceil() as usize;
+        Some(samples[rank.clamp(1, samples.len()) - 1])
+    }
+
+    pub fn summary(&self) -> Vec<String> {
+        self.stages.iter()
+            .filter_map(|Stage { name, .. }| Some(format!(
+                "{name}: {:.2} {:.2}",
+                self.average(name)?,
+                self.percentile(name, 95.0)?)))
+            .collect()
+    }
+
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "frame,stage,millis")?;
+        for Stage { name, samples } in &self.stages {
+            for (frame, millis) in samples {
+                writeln!(out, "{frame},{name},{millis}")?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn dump_csv(&self, path: &str) -> io::Result<()> {
+        self.write_csv(&mut File::create(path)?)
+    }
+
+    fn samples(&self, name: &str) -> Option<Vec<f64>> {
+        let stage = self.stages.iter().find(|stage| stage.name == name)?;
+        if stage.samples.is_empty() {
+            None
+        } else {
+            Some(stage.samples.iter().map(|(_, millis)| *millis).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn should_average_over_rolling_window() {
+        let mut profiler = Profiler::new(3);
+        for ms in [10, 1, 2, 3] {
+            profiler.record("update", millis(ms));
+        }
+
+        assert_eq!(profiler.average("update"), Some(2.0));
+    }
+
+    #[test]
+    fn should_report_nearest_rank_percentiles() {
+        let mut profiler = Profiler::new(100);
+        for ms in 1..=20 {
+            profiler.record("render batch", millis(ms));
+        }
+
+        assert_eq!(profiler.percentile("render batch", 50.0), Some(10.0));
+        assert_eq!(profiler.percentile("render batch", 95.0), Some(19.0));
+        assert_eq!(profiler.percentile("render batch", 100.0), Some(20.0));
+        assert_eq!(profiler.percentile("render batch", 0.0), Some(1.0));
+    }
+
+    #[test]
+    fn should_not_report_unknown_stages() {
+        let profiler = Profiler::new(10);
+        assert_eq!(profiler.average("present"), None);
+        assert_eq!(profiler.percentile("present", 95.0), None);
+    }
+
+    #[test]
+    fn should_keep_stages_in_order_first_recorded() {
+        let mut profiler = Profiler::new(10);
+        profiler.record("events", millis(1));
+        profiler.record("update", millis(1));
+        profiler.record("events", millis(1));
+
+        assert_eq!(profiler.stages(), vec!["events", "update"]);
+    }
+
+    #[test]
+    fn should_write_samples_as_csv() {
+        let mut profiler = Profiler::new(10);
+        profiler.record("events", millis(1));
+        profiler.end_frame();
+        profiler.record("events", millis(2));
+        profiler.record("present", millis(4));
+
+        let mut out = Vec::new();
+        profiler.write_csv(&mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "frame,stage,millis\n0,events,1\n1,events,2\n1,present,4\n");
+    }
+}
@@ -0,0 +1,424 @@
+use std::ops::RangeInclusive;
+
+use sdl2::controller::Button;
+use sdl2::event::Event as SdlEvent;
+use sdl2::keyboard::Keycode;
+
+use crate::events::Event;
+use crate::graphics::backend::RenderBackend;
+use crate::graphics::display::{PointerMoved, PointerPressed};
+use crate::graphics::layer::Layer;
+use crate::graphics::renderer::{align, Text};
+use crate::graphics::sprite::Sprite;
+
+const FOCUS_COLOUR: (u8, u8, u8) = (255, 179, 0);
+const SLIDER_LENGTH: f64 = 4.0;
+const SLIDER_NOTCHES: f64 = 8.0;
+
+// where the pieces sit on the UI sheet: the panel is the 3x3 block in the top left
+const CURSOR: (i32, i32) = (3, 0);
+const TOGGLE_OFF: (i32, i32) = (3, 1);
+const TOGGLE_ON: (i32, i32) = (4, 1);
+const SLIDER_TRACK: (i32, i32) = (3, 2);
+const SLIDER_KNOB: (i32, i32) = (4, 2);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UiInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Activate,
+    Back,
+    Point(f64, f64),
+    Click(f64, f64),
+}
+
+enum Draw {
+    Sprite(Sprite, f64, f64),
+    Text(Text, f64, f64),
+}
+
+// left, bottom, right, top, in tiles
+type Bounds = (f64, f64, f64, f64);
+
+fn contains((left, bottom, right, top): &Bounds, (x, y): (f64, f64)) -> bool {
+    x >= *left && x < *right && y >= *bottom && y < *top
+}
+
+// an immediate-mode menu: lay the widgets out again every update between begin and end, and each one reports
+// what happened to it since the last. Input is gathered from events in between, and is worked out against
+// the layout from the update before, so the pointer always hits what was on screen
+pub struct Ui {
+    sheet: String,
+    focus: usize,
+    pending: Vec<UiInput>,
+    bounds: Vec<Bounds>,
+    activated: Option<usize>,
+    click: Option<(f64, f64)>,
+    steps: i32,
+    back: bool,
+    draws: Vec<Draw>,
+}
+
+impl Ui {
+    pub fn new(sheet: &str) -> Self {
+        Ui {
+            sheet: sheet.to_string(),
+            focus: 0,
+            pending: Vec::new(),
+            bounds: Vec::new(),
+            activated: None,
+            click: None,
+            steps: 0,
+            back: false,
+            draws: Vec::new(),
+        }
+    }
+
+    pub fn input(&mut self, input: UiInput) {
+        self.pending.push(input);
+    }
+
+    pub fn on_event(&mut self, event: &Event) {
+        event.apply(|e| match e {
+            SdlEvent::KeyDown { keycode: Some(key), .. } => match key {
+                Keycode::Up => self.input(UiInput::Up),
+                Keycode::Down => self.input(UiInput::Down),
+                Keycode::Left => self.input(UiInput::Left),
+                Keycode::Right => self.input(UiInput::Right),
+                Keycode::Return | Keycode::Space => self.input(UiInput::Activate),
+                Keycode::Backspace => self.input(UiInput::Back),
+                _ => {}
+            },
+            SdlEvent::ControllerButtonDown { button, .. } => match button {
+                Button::DPadUp => self.input(UiInput::Up),
+                Button::DPadDown => self.input(UiInput::Down),
+                Button::DPadLeft => self.input(UiInput::Left),
+                Button::DPadRight => self.input(UiInput::Right),
+                Button::A => self.input(UiInput::Activate),
+                Button::B => self.input(UiInput::Back),
+                _ => {}
+            },
+            _ => {}
+        });
+        event.apply(|PointerMoved(x, y)| self.input(UiInput::Point(*x, *y)));
+        event.apply(|PointerPressed(x, y)| self.input(UiInput::Click(*x, *y)));
+    }
+
+    pub fn begin(&mut self) {
+        let count = self.bounds.len();
+        self.activated = None;
+        self.click = None;
+        self.steps = 0;
+        self.back = false;
+        for input in std::mem::take(&mut self.pending) {
+            match input {
+                UiInput::Up if count > 0 => self.focus = (self.focus + count - 1) % count,
+                UiInput::Down if count > 0 => self.focus = (self.focus + 1) % count,
+                UiInput::Left => self.steps -= 1,
+                UiInput::Right => self.steps += 1,
+                UiInput::Activate => self.activated = Some(self.focus),
+                UiInput::Back => self.back = true,
+                UiInput::Point(x, y) => {
+                    if let Some(index) = self.bounds.iter().position(|bounds| contains(bounds, (x, y))) {
+                        self.focus = index;
+                    }
+                }
+                UiInput::Click(x, y) => {
+                    if let Some(index) = self.bounds.iter().position(|bounds| contains(bounds, (x, y))) {
+                        self.focus = index;
+                        self.activated = Some(index);
+                        self.click = Some((x, y));
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.bounds.clear();
+        self.draws.clear();
+    }
+
+    // keeps focus on something that's still there if the menu got shorter
+    pub fn end(&mut self) {
+        self.focus = self.focus.min(self.bounds.len().saturating_sub(1));
+    }
+
+    pub fn back(&self) -> bool {
+        self.back
+    }
+
+    pub fn render<R: RenderBackend>(&self, renderer: &mut R) {
+        for draw in &self.draws {
+            match draw {
+                Draw::Sprite(sprite, x, y) => renderer.draw_sprite(sprite, *x, *y),
+                Draw::Text(text, x, y) => renderer.draw_text(text, *x, *y),
+            }
+        }
+    }
+
+    pub fn panel(&mut self, x: f64, y: f64, columns: u32, rows: u32) {
+        for (sprite, x, y) in nine_slice(&self.sheet, x, y, columns, rows) {
+            self.draws.push(Draw::Sprite(sprite, x, y));
+        }
+    }
+
+    pub fn label(&mut self, text: Text, x: f64, y: f64) {
+        self.draws.push(Draw::Text(text, x, y));
+    }
+
+    // widgets are a row one tile high, from x to x + width with its bottom at y
+    pub fn button(&mut self, label: &str, x: f64, y: f64, width: f64) -> bool {
+        let (focused, activated) = self.focusable((x, y, x + width, y + 1.0));
+        self.draws.push(Draw::Text(self.caption(label, focused, align::CENTER), x + width / 2.0, y + 0.5));
+        activated
+    }
+
+    pub fn toggle(&mut self, label: &str, value: &mut bool, x: f64, y: f64, width: f64) -> bool {
+        let (focused, activated) = self.focusable((x, y, x + width, y + 1.0));
+        let changed = activated || (focused && self.steps != 0);
+        if changed {
+            *value = !*value;
+        }
+        self.draws.push(Draw::Text(self.caption(label, focused, align::LEFT), x, y + 0.5));
+        let (sheet_x, sheet_y) = if *value { TOGGLE_ON } else { TOGGLE_OFF };
+        self.draws.push(Draw::Sprite(Sprite::new(sheet_x, sheet_y, Layer::Ui, &self.sheet), x + width - 1.0, y));
+        changed
+    }
+
+    // the track takes up the right hand end of the row and moves in notches; clicking on it jumps to the nearest
+    pub fn slider(&mut self, label: &str, value: &mut f64, range: RangeInclusive<f64>, x: f64, y: f64, width: f64) -> bool {
+        let (focused, activated) = self.focusable((x, y, x + width, y + 1.0));
+        let (min, max) = (*range.start(), *range.end());
+        let step = (max - min) / SLIDER_NOTCHES;
+        let track_left = x + width - SLIDER_LENGTH;
+        let previous = *value;
+        if focused && max > min {
+            *value += self.steps as f64 * step;
+            if let Some((click_x, _)) = self.click.filter(|_| activated) {
+                let fraction = ((click_x - track_left) / SLIDER_LENGTH).clamp(0.0, 1.0);
+                *value = min + ((fraction * (max - min)) / step).round() * step;
+            }
+        }
+        *value = value.clamp(min, max);
+
+        self.draws.push(Draw::Text(self.caption(label, focused, align::LEFT), x, y + 0.5));
+        for offset in 0..SLIDER_LENGTH as usize {
+            let (sheet_x, sheet_y) = SLIDER_TRACK;
+            self.draws.push(Draw::Sprite(Sprite::new(sheet_x, sheet_y, Layer::Ui, &self.sheet), track_left + offset as f64, y));
+        }
+        let fraction = if max > min { (*value - min) / (max - min) } else { 0.0 };
+        let (sheet_x, sheet_y) = SLIDER_KNOB;
+        self.draws.push(Draw::Sprite(
+            Sprite::new(sheet_x, sheet_y, Layer::Ui, &self.sheet).with_depth(1.0),
+            track_left + fraction * (SLIDER_LENGTH - 1.0),
+            y));
+        *value != previous
+    }
+
+    // a column of buttons going down from the top, saying which was chosen
+    pub fn list<S: AsRef<str>>(&mut self, items: &[S], x: f64, top: f64, width: f64) -> Option<usize> {
+        let mut chosen = None;
+        for (index, item) in items.iter().enumerate() {
+            if self.button(item.as_ref(), x, top - 1.0 - index as f64, width) {
+                chosen = Some(index);
+            }
+        }
+        chosen
+    }
+
+    fn focusable(&mut self, bounds: Bounds) -> (bool, bool) {
+        let index = self.bounds.len();
+        let (left, bottom, _, _) = bounds;
+        self.bounds.push(bounds);
+        let focused = index == self.focus;
+        if focused {
+            let (sheet_x, sheet_y) = CURSOR;
+            self.draws.push(Draw::Sprite(Sprite::new(sheet_x, sheet_y, Layer::Ui, &self.sheet), left - 1.0, bottom));
+        }
+        (focused, self.activated == Some(index))
+    }
+
+    fn caption(&self, label: &str, focused: bool, justification: u8) -> Text {
+        let text = Text::plain(label, justification | align::MIDDLE);
+        if focused {
+            let (r, g, b) = FOCUS_COLOUR;
+            text.coloured(0..label.chars().count(), r, g, b)
+        } else {
+            text
+        }
+    }
+}
+
+// a panel of whole tiles: corners and edges come from the outside of the sheet's top left 3x3 block and
+// the middle tile fills in the rest, so it stretches to any size without smearing the border
+pub fn nine_slice(sheet: &str, x: f64, y: f64, columns: u32, rows: u32) -> Vec<(Sprite, f64, f64)> {
+    let slice = |index: u32, count: u32| if index == 0 { 0 } else if index + 1 == count { 2 } else { 1 };
+    let mut sprites = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            // the sheet counts rows down from the top, the screen counts them up from the bottom
+            let sheet_y = 2 - slice(row, rows);
+            sprites.push((Sprite::new(slice(column, columns), sheet_y, Layer::Ui, sheet), x + column as f64, y + row as f64));
+        }
+    }
+    sprites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu(ui: &mut Ui) -> Vec<bool> {
+        ui.begin();
+        let pressed = vec![ui.button("one", 10.0, 5.0, 6.0), ui.button("two", 10.0, 4.0, 6.0), ui.button("three", 10.0, 3.0, 6.0)];
+        ui.end();
+        pressed
+    }
+
+    #[test]
+    fn should_move_focus_and_wrap() {
+        let mut ui = Ui::new("Ui");
+        menu(&mut ui);
+
+        ui.input(UiInput::Up);
+        ui.input(UiInput::Activate);
+        assert_eq!(menu(&mut ui), vec![false, false, true]);
+
+        ui.input(UiInput::Down);
+        ui.input(UiInput::Down);
+        ui.input(UiInput::Activate);
+        assert_eq!(menu(&mut ui), vec![false, true, false]);
+    }
+
+    #[test]
+    fn should_only_report_a_press_once() {
+        let mut ui = Ui::new("Ui");
+        menu(&mut ui);
+        ui.input(UiInput::Activate);
+
+        assert_eq!(menu(&mut ui), vec![true, false, false]);
+        assert_eq!(menu(&mut ui), vec![false, false, false]);
+    }
+
+    #[test]
+    fn should_focus_and_press_with_the_pointer() {
+        let mut ui = Ui::new("Ui");
+        menu(&mut ui);
+
+        ui.input(UiInput::Point(12.0, 3.5));
+        ui.input(UiInput::Activate);
+        assert_eq!(menu(&mut ui), vec![false, false, true]);
+
+        ui.input(UiInput::Click(15.5, 4.2));
+        assert_eq!(menu(&mut ui), vec![false, true, false]);
+
+        ui.input(UiInput::Click(2.0, 4.2));
+        assert_eq!(menu(&mut ui), vec![false, false, false]);
+    }
+
+    #[test]
+    fn should_keep_focus_within_a_shorter_menu() {
+        let mut ui = Ui::new("Ui");
+        menu(&mut ui);
+        ui.input(UiInput::Up);
+        menu(&mut ui);
+
+        ui.begin();
+        ui.button("only", 10.0, 5.0, 6.0);
+        ui.end();
+        ui.input(UiInput::Activate);
+        ui.begin();
+
+        assert!(ui.button("only", 10.0, 5.0, 6.0));
+    }
+
+    #[test]
+    fn should_flip_toggles() {
+        let mut ui = Ui::new("Ui");
+        let mut value = false;
+        ui.begin();
+        assert!(!ui.toggle("sound", &mut value, 10.0, 5.0, 6.0));
+
+        ui.input(UiInput::Activate);
+        ui.begin();
+        assert!(ui.toggle("sound", &mut value, 10.0, 5.0, 6.0));
+        assert!(value);
+
+        ui.input(UiInput::Left);
+        ui.begin();
+        assert!(ui.toggle("sound", &mut value, 10.0, 5.0, 6.0));
+        assert!(!value);
+    }
+
+    #[test]
+    fn should_step_sliders_within_their_range() {
+        let mut ui = Ui::new("Ui");
+        let mut volume = 6.0;
+        ui.begin();
+        ui.slider("volume", &mut volume, 0.0..=8.0, 10.0, 5.0, 8.0);
+
+        ui.input(UiInput::Right);
+        ui.input(UiInput::Right);
+        ui.input(UiInput::Right);
+        ui.begin();
+        assert!(ui.slider("volume", &mut volume, 0.0..=8.0, 10.0, 5.0, 8.0));
+        assert_eq!(volume, 8.0);
+
+        ui.input(UiInput::Left);
+        ui.begin();
+        ui.slider("volume", &mut volume, 0.0..=8.0, 10.0, 5.0, 8.0);
+        assert_eq!(volume, 7.0);
+    }
+
+    #[test]
+    fn should_jump_sliders_to_a_click_on_the_track() {
+        let mut ui = Ui::new("Ui");
+        let mut volume = 8.0;
+        ui.begin();
+        ui.slider("volume", &mut volume, 0.0..=8.0, 10.0, 5.0, 8.0);
+
+        ui.input(UiInput::Click(15.1, 5.5));
+        ui.begin();
+        assert!(ui.slider("volume", &mut volume, 0.0..=8.0, 10.0, 5.0, 8.0));
+        assert_eq!(volume, 2.0);
+    }
+
+    #[test]
+    fn should_say_which_list_item_was_chosen() {
+        let mut ui = Ui::new("Ui");
+        let items = ["Blue", "Redd", "Scores"];
+        ui.begin();
+        ui.list(&items, 10.0, 8.0, 6.0);
+
+        ui.input(UiInput::Click(11.0, 6.5));
+        ui.begin();
+        assert_eq!(ui.list(&items, 10.0, 8.0, 6.0), Some(1));
+    }
+
+    #[test]
+    fn should_report_going_back() {
+        let mut ui = Ui::new("Ui");
+        ui.input(UiInput::Back);
+        ui.begin();
+        assert!(ui.back());
+
+        ui.begin();
+        assert!(!ui.back());
+    }
+
+    #[test]
+    fn should_slice_panels_into_corners_edges_and_middle() {
+        let cells: Vec<(i32, i32, f64, f64)> = nine_slice("Ui", 2.0, 3.0, 4, 3).iter()
+            .map(|(sprite, x, y)| (sprite.x, sprite.y, *x, *y))
+            .collect();
+
+        assert_eq!(cells.len(), 12);
+        assert_eq!(cells[0], (0, 2, 2.0, 3.0));
+        assert_eq!(cells[1], (1, 2, 3.0, 3.0));
+        assert_eq!(cells[3], (2, 2, 5.0, 3.0));
+        assert_eq!(cells[5], (1, 1, 3.0, 4.0));
+        assert_eq!(cells[8], (0, 0, 2.0, 5.0));
+        assert_eq!(cells[11], (2, 0, 5.0, 5.0));
+    }
+}
@@ -1,12 +1,19 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use sdl2::controller::GameController;
 use sdl2::event::Event as SdlEvent;
 use sdl2::keyboard::Keycode;
 use sdl2::GameControllerSubsystem;
 use sdl2::VideoSubsystem;
 
+use engine::audio::backend::AudioBackend;
+use engine::error::EngineError;
 use engine::events::*;
 use engine::game_loop::*;
-use engine::graphics::renderer::Renderer;
+use engine::graphics::backend::RenderBackend;
+use engine::graphics::capture::{SaveRecording, TakeScreenshot};
+use engine::graphics::transition::{Transition, TransitionKind};
+use engine::profiler::{DumpProfile, ToggleProfilerOverlay};
 
 use crate::controller::Controller;
 use crate::game::game::Game;
@@ -16,7 +23,7 @@ use crate::screens::title::Title;
 
 use super::assets::Assets;
 use super::events::UpdateHiScores;
-use super::events::{GameOver, NewGame, ShowHighScores, ShowTitleScreen};
+use super::events::{ClearAudio, GameOver, NewGame, ShowHighScores, ShowTitleScreen, ToggleCollisionOverlay};
 
 #[derive(Clone)]
 pub struct HiScore {
@@ -27,11 +34,12 @@ pub struct HiScore {
 pub struct App<'a> {
     pub video_subsystem: VideoSubsystem,
     pub game_controller_subsystem: GameControllerSubsystem,
-    // pub audio_device: AudioDevice<AudioPlayer>,
+    pub audio: Box<dyn AudioBackend>,
     pub active_controller: Option<GameController>,
     pub controller: Controller,
-    pub assets: &'a Assets<'a>,
+    pub assets: &'a Assets,
     pub screen: Screen<'a>,
+    pub transition: Option<(Transition, Screen<'a>)>,
     pub scores: Vec<HiScore>,
 }
 
@@ -50,11 +58,29 @@ impl<'a> App<'a> {
             HiScore { name: "Jake".to_string(), score: 100 },
         ]
     }
+
+    fn change_screen(&mut self, screen: Screen<'a>, kind: TransitionKind) {
+        let outgoing = std::mem::replace(&mut self.screen, screen);
+        self.transition = Some((Transition::new(kind, Duration::from_millis(600)), outgoing));
+    }
+
+    fn update_transition(&mut self, dt: &Duration) {
+        if let Some((transition, _)) = &mut self.transition {
+            transition.update(dt);
+            if transition.is_finished() {
+                self.transition = None;
+            }
+        }
+    }
 }
 
-impl<'a> GameLoop<'a, Renderer<'a>> for App<'a> {
-    fn render(&self, renderer: &mut Renderer<'a>) -> Result<(), String> {
-        renderer.clear().unwrap();
+impl<'a, R: RenderBackend> GameLoop<'a, R> for App<'a> {
+    fn render(&self, renderer: &mut R) -> Result<(), EngineError> {
+        if let Some((transition, outgoing)) = &self.transition {
+            return transition.render(renderer, outgoing, &self.screen);
+        }
+
+        renderer.clear()?;
 
         self.screen.render(renderer)?;
 
@@ -63,26 +89,40 @@ impl<'a> GameLoop<'a, Renderer<'a>> for App<'a> {
         Ok(())
     }
 
-    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), String> {
+    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), EngineError> {
         if let Some(e) = event.unwrap() {
             match e {
-                SdlEvent::Quit { .. } => return Err("Escape pressed: ending game".into()),
-                SdlEvent::KeyDown { keycode: Some(Keycode::Escape), .. } => return Err("Esc pressed: ending game".into()),
+                SdlEvent::Quit { .. } => events.fire(Quit),
+                SdlEvent::KeyDown { keycode: Some(Keycode::Escape), .. } => events.fire(Quit),
+                SdlEvent::KeyDown { keycode: Some(Keycode::F2), .. } => events.fire(ToggleCollisionOverlay),
+                SdlEvent::KeyDown { keycode: Some(Keycode::F3), .. } => events.fire(ToggleProfilerOverlay),
+                SdlEvent::KeyDown { keycode: Some(Keycode::F4), .. } => events.fire(DumpProfile("profile.csv".to_string())),
+                SdlEvent::KeyDown { keycode: Some(Keycode::F5), .. } => events.fire(TakeScreenshot(capture_name("screenshot", "png"))),
+                SdlEvent::KeyDown { keycode: Some(Keycode::F6), .. } => events.fire(SaveRecording(capture_name("recording", "gif"))),
                 SdlEvent::ControllerDeviceAdded { which, .. } => {
                     self.active_controller = self.game_controller_subsystem.open(*which).ok();
                 }
                 _ => {}
             }
         }
-        // event.apply(|ClearAudio()| { self.audio_device.lock().clear(); } );
-        // event.apply(|tune| play_tune(&mut self.audio_device, tune));
-        event.apply(|NewGame(panda_type)| { self.screen = Screen::GameScreen(Game::new(*panda_type, self.assets, events)) });
-        event.apply(|GameOver(score)| { self.screen = Screen::HiScoreScreen(Scores::new(*score, self.scores.clone())) });
-        event.apply(|ShowHighScores()| { self.screen = Screen::HiScoreScreen(Scores::new(0, self.scores.clone())) });
-        event.apply(|ShowTitleScreen()| { self.screen = Screen::TitleScreen(Title) });
+        event.apply(|ClearAudio()| self.audio.clear());
+        event.apply(|tune| self.audio.play_tune(tune));
+        event.apply(|NewGame(panda_type)| {
+            let game = Game::new(*panda_type, self.assets, events);
+            self.change_screen(Screen::GameScreen(game), TransitionKind::Iris)
+        });
+        event.apply(|GameOver(score)| { self.change_screen(Screen::HiScoreScreen(Scores::new(*score, self.scores.clone())), TransitionKind::Fade) });
+        event.apply(|ShowHighScores()| { self.change_screen(Screen::HiScoreScreen(Scores::new(0, self.scores.clone())), TransitionKind::Wipe) });
+        event.apply(|ShowTitleScreen()| { self.change_screen(Screen::TitleScreen(Title::default()), TransitionKind::Dissolve) });
+        event.apply(|dt| self.update_transition(dt));
         event.apply(|UpdateHiScores(scores)| { self.scores = scores.clone() });
 
         self.controller.on_event(event, events);
-        self.screen.event(event, events)
+        GameLoop::<R>::event(&mut self.screen, event, events)
     }
-}
\ No newline at end of file
+}
+
+fn capture_name(prefix: &str, extension: &str) -> String {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    format!("{prefix}-{timestamp}.{extension}")
+}
@@ -1,11 +1,24 @@
+use crate::entities::hero::{PandaType, Poses};
+use crate::game::game::HudSprites;
+use engine::error::EngineError;
+use engine::graphics::aseprite::Aseprite;
+use engine::graphics::frames::SpriteFrames;
+use engine::graphics::palette::{swap_palette, variant_name, Palette};
 use engine::graphics::sprite::SpriteSheet;
-use image::RgbImage;
-use sdl2::image::LoadTexture;
+use image::{RgbImage, RgbaImage};
 use sdl2::render::TextureCreator;
 use sdl2::video::WindowContext;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use tiled::{Map, PropertyValue, TileId};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tiled::{Map, Properties, PropertyValue, TileId};
+
+// room for every sheet and tileset the game has on a single texture
+const ATLAS_SIZE: u32 = 1024;
+
+// the sprites are drawn in Blue's colours, and these are where they and Redd's sit in the game's palette
+const BLUE_COLOURS: [usize; 3] = [9, 8, 24];
+const REDD_COLOURS: [usize; 3] = [14, 13, 26];
 
 #[derive(Hash, Eq, PartialEq, Debug)]
 pub struct TileRef {
@@ -17,6 +30,7 @@ pub struct TileDef {
     pub x: u32,
     pub y: u32,
     pub user_type: Option<String>,
+    pub animation: Vec<(u32, u32, Duration)>,
 }
 
 pub struct TileSet(HashMap<TileRef, TileDef>);
@@ -28,42 +42,75 @@ impl TileSet {
     }
 }
 
+pub struct LevelLayer {
+    pub tiles: HashMap<(u32, u32), TileRef>,
+    pub parallax: (f64, f64),
+    pub scroll: (f64, f64),
+    pub repeat: bool,
+}
+
+impl LevelLayer {
+    pub fn is_backdrop(&self) -> bool {
+        self.parallax != (1.0, 1.0) || self.scroll != (0.0, 0.0) || self.repeat
+    }
+}
+
 pub struct Level {
+    pub width: u32,
+    pub height: u32,
     pub next_level: Option<String>,
     pub next_bonus: Option<String>,
-    pub layers: Vec<HashMap<(u32, u32), TileRef>>,
+    // how much of the daylight is missing, from 0 up to 1 for pitch black; only lights can be seen by then
+    pub darkness: f64,
+    pub layers: Vec<LevelLayer>,
 }
 
-pub struct Assets<'a> {
+pub struct Assets {
     pub countdown: RgbImage,
     pub go: RgbImage,
-    pub sheets: HashMap<String, SpriteSheet<'a>>,
     pub tiles: TileSet,
+    pub panda_poses: Poses,
+    pub redd_poses: Poses,
+    pub hud: HudSprites,
     pub levels: HashMap<String, Level>,
 }
 
-impl<'a> Assets<'a> {
-    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Result<Self, String> {
-        let assets = find_folder::Search::ParentsThenKids(3, 3).for_folder("assets").unwrap();
+impl Assets {
+    pub fn new<'a>(texture_creator: &'a TextureCreator<WindowContext>, sheets: &mut HashMap<String, SpriteSheet<'a>>) -> Result<Self, EngineError> {
+        let assets = find_folder::Search::ParentsThenKids(3, 3).for_folder("assets")
+            .map_err(|err| EngineError::Asset(err.to_string()))?;
 
         let graphics = assets.join("graphics");
 
-        let spritesheet = texture_creator.load_texture(graphics.join("spritesheet.png"))?;
-        let spritefont = texture_creator.load_texture(graphics.join("spritefont.png"))?;
-        let countdown = image::open(graphics.join("countdown.png")).unwrap().to_rgb8();
-        let go = image::open(graphics.join("go.png")).unwrap().to_rgb8();
+        let mut frames = load_frames(&graphics)?;
+        let sprite_image = sheet_image(&graphics, "spritesheet", "Sprites", (12, 12), &mut frames)?;
+        let spritefont = sheet_image(&graphics, "spritefont", "Text", (8, 8), &mut frames)?;
+        let spritefont_small = sheet_image(&graphics, "spritefont_small", "SmallText", (4, 6), &mut frames)?;
+        let ui = sheet_image(&graphics, "ui", "Ui", (12, 12), &mut frames)?;
+        // looked up now so a missing frame stops the game loading rather than a level starting
+        let panda_poses = Poses::new(&frames, PandaType::GiantPanda)?;
+        let redd_poses = Poses::new(&frames, PandaType::RedPanda)?;
+        let hud = HudSprites::new(&frames)?;
+        let countdown = image::open(graphics.join("countdown.png")).map_err(|err| EngineError::Asset(err.to_string()))?.to_rgb8();
+        let go = image::open(graphics.join("go.png")).map_err(|err| EngineError::Asset(err.to_string()))?.to_rgb8();
 
-        let mut sheets = HashMap::new();
+        let game_palette = Aseprite::open(graphics.join("pandamonium_palette.aseprite"))?.palette;
+        let blue_palette = palette_colours(&game_palette, &BLUE_COLOURS)?;
+        let redd_palette = palette_colours(&game_palette, &REDD_COLOURS)?;
 
-        sheets.insert("Sprites".to_string(), SpriteSheet::new(spritesheet, 12, 12));
-        sheets.insert("Text".to_string(), SpriteSheet::new(spritefont, 8, 8));
+        let mut images = HashMap::new();
+        images.insert(variant_name("Sprites", "Redd"), (swap_palette(&sprite_image, &blue_palette, &redd_palette), (12, 12)));
+        images.insert("Sprites".to_string(), (sprite_image, (12, 12)));
+        images.insert("Text".to_string(), (spritefont, (8, 8)));
+        images.insert("SmallText".to_string(), (spritefont_small, (4, 6)));
+        images.insert("Ui".to_string(), (ui, (12, 12)));
         let mut tiles = HashMap::new();
         let mut levels = HashMap::new();
 
         let mut map_loader = tiled::Loader::new();
 
         let mut map_files: Vec<PathBuf> = assets.join("maps").read_dir()
-            .map_err(|err| format!("{err:?}"))?
+            .map_err(|err| EngineError::Asset(format!("{err:?}")))?
             .flatten()
             .map(|dir_entry| dir_entry.path())
             .filter(|path| path.extension().map_or(false, |ext| ext == "tmx"))
@@ -73,52 +120,104 @@ impl<'a> Assets<'a> {
 
         for map_file in map_files {
             let map_name : String = map_file.file_stem().map(|fs| fs.to_str()).unwrap().unwrap().to_string();
-            let tile_map = map_loader.load_tmx_map(map_file).map_err(|err| format!("{err:?}"))?;
-            load_level(map_name, tile_map, texture_creator, &mut sheets, &mut tiles, &mut levels)?;
+            let tile_map = map_loader.load_tmx_map(map_file).map_err(|err| EngineError::Asset(format!("{err:?}")))?;
+            load_level(map_name, tile_map, &mut images, &mut tiles, &mut levels)?;
         }
 
+        // every sheet shares one texture, with the tilesets, unless they outgrow it
+        sheets.extend(SpriteSheet::atlas(texture_creator, images, ATLAS_SIZE)?);
+
         Ok(Assets {
             countdown,
             go,
-            sheets,
             tiles: TileSet(tiles),
+            panda_poses,
+            redd_poses,
+            hud,
             levels,
         })
     }
+
+    pub fn poses(&self, panda_type: PandaType) -> &Poses {
+        match panda_type {
+            PandaType::GiantPanda => &self.panda_poses,
+            PandaType::RedPanda => &self.redd_poses,
+        }
+    }
+}
+
+// every .frames file alongside the sheets names frames on them, whichever sheet they're for
+fn load_frames(graphics: &Path) -> Result<SpriteFrames, EngineError> {
+    let mut frame_files: Vec<PathBuf> = graphics.read_dir()
+        .map_err(|err| EngineError::Asset(format!("{err:?}")))?
+        .flatten()
+        .map(|dir_entry| dir_entry.path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "frames"))
+        .collect();
+
+    frame_files.sort();
+
+    let mut frames = SpriteFrames::new();
+    for frame_file in frame_files {
+        let text = std::fs::read_to_string(&frame_file).map_err(|err| EngineError::Asset(format!("{err:?}")))?;
+        frames.load(&frame_file.display().to_string(), &text)?;
+    }
+    Ok(frames)
 }
 
-fn load_level<'a>(
+// a sheet is built straight from its Aseprite file when there is one, so there's no export to forget, and the
+// file's tags become animations; otherwise it's read from the exported PNG
+fn sheet_image(graphics: &Path, file: &str, sheet: &str, (cell_width, cell_height): (u32, u32), frames: &mut SpriteFrames) -> Result<RgbaImage, EngineError> {
+    let aseprite = graphics.join(format!("{file}.aseprite"));
+    if aseprite.exists() {
+        let aseprite = Aseprite::open(aseprite)?;
+        aseprite.add_frames(frames, sheet, cell_width, cell_height);
+        Ok(aseprite.sheet_image())
+    } else {
+        Ok(image::open(graphics.join(format!("{file}.png"))).map_err(|err| EngineError::Asset(err.to_string()))?.to_rgba8())
+    }
+}
+
+// other palettes are swapped in when the sheet loads, colour for colour
+fn palette_colours(Palette(colours): &Palette, indices: &[usize]) -> Result<Palette, EngineError> {
+    indices.iter()
+        .map(|index| colours.get(*index).copied()
+            .ok_or_else(|| EngineError::Asset(format!("pandamonium_palette.aseprite has no colour {index}"))))
+        .collect::<Result<Vec<_>, EngineError>>()
+        .map(Palette)
+}
+
+fn load_level(
     map_name: String,
     tile_map: Map,
-    texture_creator: &'a TextureCreator<WindowContext>,
-    sheets: &mut HashMap<String, SpriteSheet<'a>>,
+    images: &mut HashMap<String, (RgbaImage, (u32, u32))>,
     tiles: &mut HashMap<TileRef, TileDef>,
     levels: &mut HashMap<String, Level>
-) -> Result<(), String> {
-    let next_level: Option<String> = get_string_property(&tile_map, "next_level");
-    let next_bonus: Option<String> = get_string_property(&tile_map, "next_bonus");
+) -> Result<(), EngineError> {
+    let next_level: Option<String> = get_string_property(&map_name, &tile_map.properties, "next_level")?;
+    let next_bonus: Option<String> = get_string_property(&map_name, &tile_map.properties, "next_bonus")?;
+    let darkness = get_float_property(&map_name, &tile_map.properties, "darkness")?.unwrap_or(0.0);
     for tileset in tile_map.tilesets() {
         let sheet = tileset.name.to_string();
 
-        if sheets.contains_key(&sheet) {
+        if images.contains_key(&sheet) {
             continue;
         }
 
         let columns = tileset.columns;
 
         for (tile_id, tile) in tileset.tiles() {
+            let animation = tile.animation.iter().flatten()
+                .map(|frame| (frame.tile_id % columns, frame.tile_id / columns, Duration::from_millis(frame.duration as u64)))
+                .collect();
             tiles.insert(
                 TileRef { sheet: sheet.clone(), tile_id },
-                TileDef { x: tile_id % columns, y: tile_id / columns, user_type: tile.user_type.clone() });
+                TileDef { x: tile_id % columns, y: tile_id / columns, user_type: tile.user_type.clone(), animation });
         }
 
         if let Some(image) = &tileset.image {
-            sheets.insert(
-                sheet,
-                SpriteSheet::new(
-                    texture_creator.load_texture(&image.source)?,
-                    tileset.tile_width,
-                    tileset.tile_height));
+            let image = image::open(&image.source).map_err(|err| EngineError::Asset(format!("{}: {err}", image.source.display())))?.to_rgba8();
+            images.insert(sheet, (image, (tileset.tile_width, tileset.tile_height)));
         }
     }
 
@@ -126,9 +225,10 @@ fn load_level<'a>(
 
     for layer in tile_map.layers() {
         if let Some(tiles) = layer.as_tile_layer() {
+            let layer_name = format!("{map_name} {}", layer.name);
             let mut map_layer = HashMap::new();
-            let width = tiles.width().unwrap();
-            let height = tiles.height().unwrap();
+            let (width, height) = tiles.width().zip(tiles.height())
+                .ok_or_else(|| EngineError::Asset(format!("{map_name} has an infinite tile layer")))?;
             for x in 0..width {
                 for y in 0..height {
                     if let Some(tile) = tiles.get_tile(x as i32, y as i32) {
@@ -139,17 +239,39 @@ fn load_level<'a>(
                     }
                 }
             }
-            layers.push(map_layer);
+            layers.push(LevelLayer {
+                tiles: map_layer,
+                parallax: (layer.parallax_x as f64, layer.parallax_y as f64),
+                scroll: (
+                    get_float_property(&layer_name, &layer.properties, "scroll_x")?.unwrap_or(0.0),
+                    get_float_property(&layer_name, &layer.properties, "scroll_y")?.unwrap_or(0.0)),
+                repeat: get_bool_property(&layer_name, &layer.properties, "repeat")?.unwrap_or(false),
+            });
         }
     }
 
-    levels.insert(map_name, Level { next_level, next_bonus, layers });
+    levels.insert(map_name, Level { width: tile_map.width, height: tile_map.height, next_level, next_bonus, darkness, layers });
     Ok(())
 }
 
-fn get_string_property(tile_map: &Map, property: &str) -> Option<String> {
-    tile_map.properties.get(property).map(|pv| match pv {
-        PropertyValue::StringValue(val) => val.clone(),
-        _ => panic!("Non-string value for {}", property)
-    })
-}
\ No newline at end of file
+fn get_string_property(map_name: &str, properties: &Properties, property: &str) -> Result<Option<String>, EngineError> {
+    properties.get(property).map(|pv| match pv {
+        PropertyValue::StringValue(val) => Ok(val.clone()),
+        _ => Err(EngineError::Asset(format!("{map_name}: {property} should be a string")))
+    }).transpose()
+}
+
+fn get_float_property(map_name: &str, properties: &Properties, property: &str) -> Result<Option<f64>, EngineError> {
+    properties.get(property).map(|pv| match pv {
+        PropertyValue::FloatValue(val) => Ok(*val as f64),
+        PropertyValue::IntValue(val) => Ok(*val as f64),
+        _ => Err(EngineError::Asset(format!("{map_name}: {property} should be a number")))
+    }).transpose()
+}
+
+fn get_bool_property(map_name: &str, properties: &Properties, property: &str) -> Result<Option<bool>, EngineError> {
+    properties.get(property).map(|pv| match pv {
+        PropertyValue::BoolValue(val) => Ok(*val),
+        _ => Err(EngineError::Asset(format!("{map_name}: {property} should be a boolean")))
+    }).transpose()
+}
@@ -2,7 +2,7 @@ use component_derive::Event;
 use engine::events::EventTrait;
 
 use crate::entities::components::Interacts;
-use crate::entities::hero::PandaType;
+use crate::entities::hero::{PandaType, Poses};
 
 use super::app::HiScore;
 
@@ -49,7 +49,7 @@ pub struct SpawnBulb(pub f64, pub f64);
 pub struct SpawnFlashBulb(pub f64, pub f64);
 
 #[derive(Event)]
-pub struct SpawnHero(pub f64, pub f64, pub PandaType);
+pub struct SpawnHero(pub f64, pub f64, pub Poses);
 
 #[derive(Event)]
 pub struct ClearAudio();
@@ -57,6 +57,9 @@ pub struct ClearAudio();
 #[derive(Event)]
 pub struct GameOver(pub u32);
 
+#[derive(Event)]
+pub struct ToggleCollisionOverlay;
+
 #[derive(Event)]
 pub struct Interaction {
     pub hero_id: u64,
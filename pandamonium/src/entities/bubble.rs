@@ -1,5 +1,6 @@
 use std::time::Duration;
 use component_derive::{Constant, Event};
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use entity::{entity, Entities};
 use engine::events::*;
@@ -18,10 +19,10 @@ pub struct BubbleHit(pub (f64, f64), pub u64);
 pub fn spawn_bubble(x: f64, y: f64, entities: &mut Entities) {
     let phase = phase_offset(x, y);
     let animation_cycle = AnimationCycle(vec!(
-        (0.25, Sprite::new(5, 3, 0.5, "Sprites")),
-        (0.5, Sprite::new(6, 3, 0.5, "Sprites")),
-        (0.75, Sprite::new(5, 3, 0.5, "Sprites")),
-        (1.0, Sprite::new(7, 3, 0.5, "Sprites"))));
+        (0.25, Sprite::new(5, 3, Layer::Items, "Sprites")),
+        (0.5, Sprite::new(6, 3, Layer::Items, "Sprites")),
+        (0.75, Sprite::new(5, 3, Layer::Items, "Sprites")),
+        (1.0, Sprite::new(7, 3, Layer::Items, "Sprites"))));
     entities.spawn(entity()
         .with(Bubble)
         .with(Position(x, y))
@@ -37,11 +38,11 @@ pub fn bubble_hit(BubbleHit((_px, py), id): &BubbleHit, events: &mut Events, ent
     if let Some(Position(x, y)) = entities.delete(&id) {
         let pop_id = entities.spawn(entity()
             .with(AnimationCycle(vec!(
-                (0.33, Sprite::new(4, 1, 0.5, "Sprites")),
-                (0.66, Sprite::new(5, 1, 0.5, "Sprites")),
-                (1.00, Sprite::new(6, 1, 0.5, "Sprites")),
+                (0.33, Sprite::new(4, 1, Layer::Items, "Sprites")),
+                (0.66, Sprite::new(5, 1, Layer::Items, "Sprites")),
+                (1.00, Sprite::new(6, 1, Layer::Items, "Sprites")),
             )))
-            .with(Sprite::new(4, 1, 0.5, "Sprites"))
+            .with(Sprite::new(4, 1, Layer::Items, "Sprites"))
             .with(Phase(0.0))
             .with(Period(0.3))
             .with(Position(x, y))
@@ -1,4 +1,5 @@
 use component_derive::{Constant, Event};
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use entity::{entity, Entities};
 use std::time::Duration;
@@ -22,14 +23,14 @@ pub fn spawn_chest(x: f64, y: f64, entities: &mut Entities) {
     entities.spawn(entity()
         .with(Chest)
         .with(Position(x, y))
-        .with(Sprite::new(2, 7, 0.5, "Sprites"))
+        .with(Sprite::new(2, 7, Layer::Items, "Sprites"))
     );
 }
 
 pub fn spawn_open_chest(x: f64, y: f64, entities: &mut Entities, events: &mut Events) {
     let chest_id = entities.spawn(entity()
         .with(Position(x, y))
-        .with(Sprite::new(3, 7, 0.5, "Sprites"))
+        .with(Sprite::new(3, 7, Layer::Items, "Sprites"))
         .with(TranslatedMesh(Shape::bbox(0.0, 0.0, 1.0, 1.0).translate(&(x, y))))
     );
 
@@ -43,7 +44,7 @@ pub fn spawn_ruby(x: f64, y: f64, entities: &mut Entities) {
         .with(Position(x, y + 0.1))
         .with(Velocity(0.0, 20.0))
         .with(Gravity)
-        .with(Sprite::new(3, 8, 0.75, "Sprites"))
+        .with(Sprite::new(3, 8, Layer::Items, "Sprites").with_depth(1.0))
         .with(OnPickupDo::Score(100))
         .with(OnPickupEffect::Sparkles)
         .with(OnPickupText("100"))
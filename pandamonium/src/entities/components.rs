@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use component_derive::{Constant, Event, Variable};
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use engine::shapes::shape::shape::Shape;
 use engine::events::EventTrait;
@@ -65,6 +66,9 @@ pub enum Interacts {
 #[derive(Clone, Constant)]
 pub struct Collidable;
 
+#[derive(Clone, Constant)]
+pub struct ScreenSpace;
+
 pub fn next_frame(phase: f64, AnimationCycle(frames): &AnimationCycle) -> Sprite {
     let phase = phase % 1.0;
     for (frame_limit, sprite) in frames {
@@ -72,7 +76,7 @@ pub fn next_frame(phase: f64, AnimationCycle(frames): &AnimationCycle) -> Sprite
             return sprite.clone();
         }
     }
-    Sprite::new(0, 0, 0.0, "Sprites")
+    Sprite::new(0, 0, Layer::Items, "Sprites")
 }
 
 #[derive(Debug, Clone, Variable)]
@@ -92,4 +96,11 @@ pub struct Age(pub f64);
 
 pub fn age(dt: &Duration, entities: &mut Entities) {
     entities.apply(|Age(age)| Age(age + dt.as_secs_f64()));
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Constant)]
+pub struct Fade(pub f64);
+
+pub fn fade(entities: &mut Entities) {
+    entities.apply(|(Fade(duration), Age(age), sprite): (Fade, Age, Sprite)| sprite.faded(1.0 - age / duration));
+}
@@ -1,8 +1,11 @@
 use std::time::Duration;
 use super::components::*;
+use super::particle::spawn_debris;
 use crate::app::events::{Destroy, KeyCollected, SpawnParticle};
 use component_derive::{Constant, Event};
 use engine::events::*;
+use engine::graphics::effects::CameraShake;
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use engine::shapes::shape::shape::Shape;
 use entity::{entity, Entities, Id};
@@ -18,7 +21,7 @@ pub fn spawn_crumbler(x: f64, y: f64, entities: &mut Entities) {
         .with(Crumbler)
         .with(Obstacle)
         .with(Position(x, y))
-        .with(Sprite::new(2, 0, 0.5, "Sprites"))
+        .with(Sprite::new(2, 0, Layer::Items, "Sprites"))
         .with(TranslatedMesh(Shape::bbox(0.0, 0.0, 1.0, 1.0).translate(&(x, y))))
     );
 }
@@ -40,15 +43,17 @@ pub fn spawn_crumbles(SpawnCrumbles(x, y): &SpawnCrumbles, events: &mut Events,
     let crumbler_id = entities.spawn(entity()
         .with(Obstacle)
         .with(AnimationCycle(vec!(
-            (0.33, Sprite::new(3, 0, 0.5, "Sprites")),
-            (0.66, Sprite::new(4, 0, 0.5, "Sprites")),
-            (1.00, Sprite::new(5, 0, 0.5, "Sprites"))
+            (0.33, Sprite::new(3, 0, Layer::Items, "Sprites")),
+            (0.66, Sprite::new(4, 0, Layer::Items, "Sprites")),
+            (1.00, Sprite::new(5, 0, Layer::Items, "Sprites"))
         )))
-        .with(Sprite::new(3, 0, 0.5, "Sprites"))
+        .with(Sprite::new(3, 0, Layer::Items, "Sprites"))
         .with(Phase(0.0))
         .with(Period(1.0))
         .with(Position(*x, *y))
         .with(TranslatedMesh(Shape::bbox(0.01, 0.01, 0.98, 0.98).translate(&(*x, *y))))
     );
     events.schedule("world", Duration::from_secs_f64(1.0), Destroy(crumbler_id));
+    events.schedule("world", Duration::from_secs_f64(1.0), CameraShake(0.6, Duration::from_millis(400)));
+    spawn_debris(x + 0.5, y + 0.5, Sprite::new(2, 0, Layer::Actors, "Sprites").with_depth(-1.0), entities);
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use entity::{entity, Entities};
 
@@ -13,16 +14,16 @@ pub fn spawn_flagpole(x: f64, y: f64, bonus_exit: bool, exit_to: String, entitie
 
     entities.spawn(entity()
         .with(Position(x, y))
-        .with(Sprite::new(5, sprite_row, 0.3, "Sprites")));
+        .with(Sprite::new(5, sprite_row, Layer::Items, "Sprites").with_depth(-1.0)));
 
     let animation_cycle = AnimationCycle(vec!(
-        (0.5, Sprite::new(6, sprite_row, 0.5, "Sprites")),
-        (1.0, Sprite::new(7, sprite_row, 0.5, "Sprites"))));
+        (0.5, Sprite::new(6, sprite_row, Layer::Items, "Sprites")),
+        (1.0, Sprite::new(7, sprite_row, Layer::Items, "Sprites"))));
 
     entities.spawn(entity()
         .with(Pickup)
         .with(Position(x, y))
-        .with(Sprite::new(6, sprite_row, 0.5, "Sprites"))
+        .with(Sprite::new(6, sprite_row, Layer::Items, "Sprites"))
         .with(animation_cycle)
         .with(Period(0.2))
         .with(Phase(0.0))
@@ -1,7 +1,9 @@
-use crate::entities::components::Position;
+use crate::entities::components::{Position, ScreenSpace};
 use crate::entities::flashlamp::FBColor::YELLOW;
 use component_derive::{Event, Variable};
 use engine::events::{Event, EventTrait, Events};
+use engine::graphics::layer::Layer;
+use engine::graphics::lighting::PointLight;
 use engine::graphics::sprite::Sprite;
 use entity::{entity, Entities};
 use std::time::Duration;
@@ -34,6 +36,7 @@ pub fn spawn_flashlamp(x: f64, y: f64, fire_in: f64, entities: &mut Entities, ev
     let entity_id = entities.spawn(
         entity()
             .with(Position(x, y))
+            .with(ScreenSpace)
             .with(flashbulb_sprite(OFF))
             .with(FlashbulbLit(false))
             .with(FlashbulbColor(GREEN)),
@@ -51,22 +54,38 @@ pub fn flashbulb_events(entities: &mut Entities, event: &Event) {
         if lit { flashbulb_sprite(YELLOW) } else { sprite }
     ));
 
+    event.apply(|TurnFlashbulbsYellow| entities.apply(|(FlashbulbLit(_), _): (FlashbulbLit, PointLight)| flashbulb_light(YELLOW)));
+
     event.apply(|TurnFlashbulbsRed| entities.apply(|FlashbulbColor(_)| FlashbulbColor(RED)));
 
     event.apply(|TurnFlashbulbsRed| entities.apply(|(FlashbulbLit(lit), sprite)|
         if lit { flashbulb_sprite(RED) } else { sprite }
     ));
 
+    event.apply(|TurnFlashbulbsRed| entities.apply(|(FlashbulbLit(_), _): (FlashbulbLit, PointLight)| flashbulb_light(RED)));
+
     event.apply(|LightFlashbulb(entity_id)| entities.apply_to(entity_id, |FlashbulbColor(fb_color)|
-        (flashbulb_sprite(fb_color), FlashbulbLit(true))
+        (flashbulb_sprite(fb_color.clone()), FlashbulbLit(true), flashbulb_light(fb_color))
     ));
 }
 
+// on top of the rest of the interface
 fn flashbulb_sprite(fb_color: FBColor) -> Sprite {
+    let sprite = match fb_color {
+        GREEN => Sprite::new(7, 4, Layer::Ui, "Walls"),
+        YELLOW => Sprite::new(7, 5, Layer::Ui, "Walls"),
+        RED => Sprite::new(7, 6, Layer::Ui, "Walls"),
+        OFF => Sprite::new(6, 4, Layer::Ui, "Walls")
+    };
+    sprite.with_depth(3.0)
+}
+
+// only lit bulbs carry a light, so the border brightens as the clock runs down
+fn flashbulb_light(fb_color: FBColor) -> PointLight {
     match fb_color {
-        GREEN => Sprite::new(7, 4, 3.0, "Walls"),
-        YELLOW => Sprite::new(7, 5, 3.0, "Walls"),
-        RED => Sprite::new(7, 6, 3.0, "Walls"),
-        OFF => Sprite::new(6, 4, 3.0, "Walls")
+        GREEN => PointLight::new(1.5, (120, 255, 120)),
+        YELLOW => PointLight::new(1.5, (255, 230, 100)),
+        RED => PointLight::new(1.5, (255, 90, 90)),
+        OFF => PointLight::new(0.0, (0, 0, 0))
     }
-}
\ No newline at end of file
+}
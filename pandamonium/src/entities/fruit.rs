@@ -1,3 +1,4 @@
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use entity::{entity, Entities};
 
@@ -24,12 +25,14 @@ pub fn spawn_fruit(x: f64, y: f64, fruit: &Fruit, entities: &mut Entities) {
     );
 }
 
+// under the chest it comes out of
 fn sprite(fruit: &Fruit) -> Sprite {
-    match fruit {
-        Fruit::APPLE => Sprite::new(6, 4, 0.0, "Sprites"),
-        Fruit::BANANA => Sprite::new(5, 4, 0.0, "Sprites"),
-        Fruit::CHERRY => Sprite::new(4, 4, 0.0, "Sprites"),
-        Fruit::GRAPES => Sprite::new(4, 3, 0.0, "Sprites"),
-        Fruit::WATERMELON => Sprite::new(7, 4, 0.0, "Sprites")
-    }
+    let sprite = match fruit {
+        Fruit::APPLE => Sprite::new(6, 4, Layer::Items, "Sprites"),
+        Fruit::BANANA => Sprite::new(5, 4, Layer::Items, "Sprites"),
+        Fruit::CHERRY => Sprite::new(4, 4, Layer::Items, "Sprites"),
+        Fruit::GRAPES => Sprite::new(4, 3, Layer::Items, "Sprites"),
+        Fruit::WATERMELON => Sprite::new(7, 4, Layer::Items, "Sprites")
+    };
+    sprite.with_depth(-1.0)
 }
\ No newline at end of file
@@ -5,7 +5,12 @@ use crate::app::events::{Fail, Interaction, SpawnHero};
 use crate::controller::{ButtonPress, ControllerState};
 use crate::sign::{Sign, Signed};
 use component_derive::{Constant, Event, Variable};
+use engine::error::EngineError;
 use engine::events::*;
+use engine::graphics::animation::Animation;
+use engine::graphics::frames::SpriteFrames;
+use engine::graphics::layer::Layer;
+use engine::graphics::lighting::PointLight;
 use engine::graphics::sprite::Sprite;
 use engine::shapes::shape::shape::Shape;
 use entity::{entity, Entities};
@@ -29,16 +34,9 @@ const EXTRA_JUMP_DURATION: f64 = 0.2;
 const COYOTE_TIME: f64 = 0.1;
 const PREJUMP: f64 = 0.1;
 
-const PANDA_OFFSET: i32 = 1;
-const RED_PANDA_OFFSET: i32 = 4;
-
-const UNITS_PER_FRAME: f64 = 1.0;
-const RUN_CYCLE: [(i32, i32); 4] = [(1, 1), (2, 1), (3, 1), (2, 1)];
-const ASCENDING: (i32, i32) = (2, 0);
-const SWIMMING: (i32, i32) = (0, 2);
-const DESCENDING: (i32, i32) = (3, 0);
-const WALL_DRAGGING: (i32, i32) = (1, 0);
-const STANDING: (i32, i32) = (0, 1);
+// the run cycle goes by the distance covered rather than the time taken, so the feet don't skate:
+// each tile run plays this many seconds of it
+const RUN_CYCLE_PER_UNIT: f64 = 0.1;
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum JumpDirection {
@@ -54,12 +52,44 @@ pub enum PandaType {
     RedPanda,
 }
 
+// the red panda has its own frames rather than a palette swap, as it's drawn differently and not just recoloured
+#[derive(Clone, Variable)]
+pub struct Poses {
+    standing: Sprite,
+    running: Animation,
+    ascending: Sprite,
+    descending: Sprite,
+    swimming: Sprite,
+    wall_dragging: Sprite,
+}
+
+impl Poses {
+    pub fn new(frames: &SpriteFrames, panda_type: PandaType) -> Result<Self, EngineError> {
+        let name = match panda_type {
+            GiantPanda => "panda",
+            RedPanda => "redd",
+        };
+        let pose = |pose: &str| frames.sprite(&format!("{name}/{pose}"), Layer::Actors);
+        Ok(Poses {
+            standing: pose("stand")?,
+            running: frames.animation(&format!("{name}/run"), Layer::Actors)?,
+            ascending: pose("ascend")?,
+            descending: pose("descend")?,
+            swimming: pose("swim")?,
+            wall_dragging: pose("wall-drag")?,
+        })
+    }
+}
+
 #[derive(Clone, Constant)]
 pub struct Hero;
 
 #[derive(Clone, Variable)]
 pub struct IsInWater(pub bool);
 
+#[derive(Clone, Variable)]
+pub struct HasFallen(pub bool);
+
 #[derive(Clone, Variable)]
 pub struct MovingX(pub Sign);
 
@@ -81,7 +111,7 @@ pub struct Prejump(pub f64);
 #[derive(Event)]
 pub struct Jumped(pub JumpDirection);
 
-pub fn spawn_hero(x: f64, y: f64, panda_type: PandaType, entities: &mut Entities) {
+pub fn spawn_hero(x: f64, y: f64, poses: &Poses, entities: &mut Entities) {
     entities.spawn(entity()
         .with(Hero)
         .with(Gravity)
@@ -92,15 +122,17 @@ pub fn spawn_hero(x: f64, y: f64, panda_type: PandaType, entities: &mut Entities
         .with(ReferenceContextMesh(Shape::bbox(0.45, 0.45, 0.1, 0.1)))
         .with(TranslatedContextMesh(Shape::bbox(0.45, 0.45, 0.1, 0.1).translate(&(x, y))))
         .with(IsInWater(false))
-        .with(offset_sprite(STANDING, &panda_type, false))
+        .with(HasFallen(false))
+        .with(poses.standing.clone())
         .with(MovingX(Sign::ZERO))
         .with(Velocity(0.0, 0.0))
         .with(LastPush(0.0, 0.0))
         .with(Facing(Sign::POSITIVE))
-        .with(panda_type)
+        .with(poses.clone())
         .with(CoyoteTime(JumpDirection::NONE, 0.0))
         .with(Prejump(0.0))
         .with(Ascending(0.0))
+        .with(PointLight::new(4.0, (255, 236, 200)))
     );
 }
 
@@ -109,21 +141,23 @@ pub fn hero_events(entities: &mut Entities, event: &Event, events: &mut Events)
     event.apply(|buttonpress| jump(entities, events, buttonpress));
     event.apply(|jump| on_jump(entities, jump));
     event.apply(|dt| update_hero(entities, dt, events));
-    event.apply(|&SpawnHero(x, y, panda_type)| spawn_hero(x, y, panda_type, entities));
+    event.apply(|SpawnHero(x, y, poses)| spawn_hero(*x, *y, poses, entities));
     event.apply(|&Interaction { hero_id, interaction_type, .. }| { handle_interaction(hero_id, interaction_type, entities) });
     event.apply(|&InWater(hero_id, in_water)| { handle_in_water(hero_id, in_water, entities)});
 }
 
-pub fn clamp_hero(entities: &mut Entities, event: &Event, _events: &mut Events) {
-    event.apply(|dt| clamp_to_screen(dt, entities));
-}
-
-pub fn clamp_to_screen(_dt: &Duration, entities: &mut Entities) {
-    entities.apply(|(Hero, Position(dx, dy))| Position(dx.clamp(0.0, 27.0), dy));
+pub fn clamp_to_map(width: f64, entities: &mut Entities) {
+    entities.apply(|(Hero, Position(dx, dy))| Position(dx.clamp(0.0, width - 1.0), dy));
 }
 
 pub fn check_fail(entities: &mut Entities, _dt: &Duration, events: &mut Events) {
-    entities.apply(|(Hero, Position(_, dy))| if dy < -2.0 { events.fire(Fail) });
+    // only the once, as the hero keeps falling for a while after dropping off the map
+    entities.apply(|(Hero, Position(_, dy), HasFallen(fallen))| {
+        if dy < -2.0 && !fallen {
+            events.fire(Fail);
+        }
+        HasFallen(fallen || dy < -2.0)
+    });
 }
 
 fn handle_interaction(hero_id: u64, interaction_type: Interacts, entities: &mut Entities) {
@@ -151,26 +185,24 @@ fn update_hero(entities: &mut Entities, dt: &Duration, events: &mut Events) {
 }
 
 fn animate(entities: &mut Entities, _dt: &Duration) {
-    entities.apply(|(Hero, Position(x, _y), Velocity(dx, dy), Facing(facing), LastPush(px, py), IsInWater(iw), panda_type)| {
-        let tile = if iw {
-                SWIMMING
+    entities.apply(|(Hero, Position(x, _y), Velocity(dx, dy), Facing(facing), LastPush(px, py), IsInWater(iw), poses): (Hero, Position, Velocity, Facing, LastPush, IsInWater, Poses)| {
+        let facing_left = facing == Sign::NEGATIVE;
+        let (pose, flip_x) = if iw {
+                (&poses.swimming, facing_left)
             } else if py == 0.0 {
             if dy > 0.0 {
-                ASCENDING
+                (&poses.ascending, facing_left)
             } else if px != 0.0 {
-                WALL_DRAGGING
+                (&poses.wall_dragging, !facing_left)
             } else {
-                DESCENDING
+                (&poses.descending, facing_left)
             }
         } else if dx.abs() < STOPPING_SPEED {
-            STANDING
+            (&poses.standing, facing_left)
         } else {
-            let frame: usize = (x / UNITS_PER_FRAME) as usize % RUN_CYCLE.len();
-            RUN_CYCLE[frame]
+            (poses.running.frame_at(x * RUN_CYCLE_PER_UNIT).unwrap_or(&poses.standing), facing_left)
         };
-        let mut flip_x = facing == Sign::NEGATIVE;
-        if tile == WALL_DRAGGING { flip_x = !flip_x }
-        offset_sprite(tile, &panda_type, flip_x)
+        Sprite { flip_x, ..pose.clone() }
     });
 }
 
@@ -286,13 +318,6 @@ fn clamp(entities: &mut Entities, _dt: &Duration) {
     })
 }
 
-fn offset_sprite((x, y): (i32, i32), panda_type: &PandaType, flip_x: bool) -> Sprite {
-    Sprite::sprite(x, y + match panda_type {
-        PandaType::GiantPanda => PANDA_OFFSET,
-        PandaType::RedPanda => RED_PANDA_OFFSET
-    }, 1.0, flip_x, false, "Sprites")
-}
-
 fn jump(entities: &mut Entities, events: &mut Events, _event: &ButtonPress) {
     entities.apply(|(Hero, CoyoteTime(direction, _ct))| {
         if direction != JumpDirection::NONE {
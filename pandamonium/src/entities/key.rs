@@ -8,6 +8,7 @@ use super::pickup::Pickup;
 use engine::audio::audio::PlayTune;
 use engine::audio::instrument::*;
 use engine::audio::notes::C4;
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use engine::shapes::shape::shape::Shape;
 use entity::{entity, Entities};
@@ -16,7 +17,7 @@ pub fn spawn_key(x: f64, y: f64, entities: &mut Entities) {
     entities.spawn(entity()
         .with(Pickup)
         .with(Position(x, y))
-        .with(Sprite::new(4, 7, 0.5, "Sprites"))
+        .with(Sprite::new(4, 7, Layer::Items, "Sprites"))
         .with(OnPickupEffect::Sparkles)
         .with(OnPickupTune(PlayTune(3, vec![
             (Duration::from_millis(0), CYMBAL.note(C4, 0.5)),
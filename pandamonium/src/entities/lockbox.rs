@@ -2,6 +2,7 @@ use super::components::*;
 use crate::app::events::{Destroy, KeyCollected, SpawnParticle};
 use component_derive::Constant;
 use engine::events::*;
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use engine::shapes::shape::shape::Shape;
 use entity::{entity, Entities, Id};
@@ -14,7 +15,7 @@ pub fn spawn_lockbox(x: f64, y: f64, entities: &mut Entities) {
         .with(Lockbox)
         .with(Obstacle)
         .with(Position(x, y))
-        .with(Sprite::new(4, 8, 0.5, "Sprites"))
+        .with(Sprite::new(4, 8, Layer::Items, "Sprites"))
         .with(TranslatedMesh(Shape::bbox(0.0, 0.0, 1.0, 1.0).translate(&(x, y))))
     );
 }
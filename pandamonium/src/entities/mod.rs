@@ -27,7 +27,6 @@ use crate::app::events::*;
 use crate::controllers::physics::*;
 use crate::entities::bubble::bubble_hit;
 use crate::entities::flashlamp::flashbulb_events;
-use crate::entities::hero::clamp_hero;
 use crate::entities::radial::radial_events;
 use engine::events::{Event, Events};
 use crate::entities::crumbler::crumbler_events;
@@ -45,7 +44,7 @@ pub fn entity_events(event: &Event, entities: &mut Entities, events: &mut Events
     event.apply(|dt| gravity(entities, dt));
     event.apply(|dt| integrate(entities, dt));
     event.apply(|dt| translate(entities, dt));
-    clamp_hero(entities, event, events);
+    event.apply(|dt| update_emitters(dt, entities, events));
 
     event.apply(|Destroy(id)| entities.delete::<()>(id));
     event.apply(|pickup| { collect_pickup(pickup, entities, events) });
@@ -53,7 +52,7 @@ pub fn entity_events(event: &Event, entities: &mut Entities, events: &mut Events
 }
 
 pub fn spawn_events(event: &Event, entities: &mut Entities, events: &mut Events) {
-    event.apply(|&SpawnParticle(x, y)| spawn_spangle(x, y, entities, events));
+    event.apply(|&SpawnParticle(x, y)| spawn_spangle(x, y, entities));
     event.apply(|&SpawnText(x, y, ref text)| spawn_text(x, y, text, entities, events));
     event.apply(|&SpawnBulb(x, y)| spawn_bulb(x, y, entities, events));
     event.apply(|&SpawnFlashBulb(x, y)| spawn_flashbulb(x, y, entities, events));
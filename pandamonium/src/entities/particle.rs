@@ -1,29 +1,70 @@
 use engine::events::Events;
 use engine::graphics::renderer::{align, Text};
+use engine::graphics::layer::Layer;
+use engine::graphics::lighting::PointLight;
 use engine::graphics::sprite::Sprite;
+use engine::particles::Emitter;
 use std::time::Duration;
 
 use super::components::*;
 use crate::app::events::Destroy;
-use entity::{entity, Entities};
+use entity::{entity, Entities, Id};
 
-pub fn spawn_spangle(x: f64, y: f64, entities: &mut Entities, events: &mut Events) {
-    let spangle_id = entities.spawn(entity()
+pub fn spawn_spangle(x: f64, y: f64, entities: &mut Entities) {
+    entities.spawn(entity()
+        .with(Position(x + 0.25, y + 0.25))
+        .with(Emitter::new(vec![
+            Sprite::new(0, 7, Layer::Effects, "Sprites").scaled(0.5, 0.5),
+            Sprite::new(1, 7, Layer::Effects, "Sprites").scaled(0.5, 0.5),
+            Sprite::new(0, 7, Layer::Effects, "Sprites").scaled(0.5, 0.5),
+        ])
+            .with_burst(6)
+            .with_lifetime(0.3, 0.45)
+            .with_speed(2.0, 4.0)
+            .with_cone(90.0, 120.0)
+            .with_gravity(8.0)
+            .with_duration(0.0))
+    );
+}
+
+pub fn spawn_debris(x: f64, y: f64, sprite: Sprite, entities: &mut Entities) {
+    entities.spawn(entity()
         .with(Position(x, y))
-        .with(Sprite::new(0, 7, 0.5, "Sprites"))
-        .with(Period(0.45))
-        .with(Phase(0.0))
-        .with(AnimationCycle(vec![(0.33, Sprite::new(0, 7, 0.5, "Sprites")), (0.66, Sprite::new(1, 7, 0.5, "Sprites")), (1.0, Sprite::new(0, 7, 0.5, "Sprites"))]))
+        .with(Emitter::new(vec![sprite.scaled(0.25, 0.25)])
+            .with_burst(5)
+            .with_lifetime(0.6, 0.9)
+            .with_speed(1.0, 3.0)
+            .with_cone(90.0, 160.0)
+            .with_gravity(20.0)
+            .with_duration(0.0))
     );
+}
 
-    events.schedule("world", Duration::from_millis(450), Destroy(spangle_id));
+pub fn update_emitters(dt: &Duration, entities: &mut Entities, events: &mut Events) {
+    entities.apply(|(Position(x, y), mut emitter): (Position, Emitter)| {
+        emitter.update(dt, x, y);
+        emitter
+    });
+    entities.for_each(|(Id(id), emitter): (Id, Emitter)| if emitter.is_finished() { events.fire(Destroy(id)) });
+}
+
+pub fn spawn_fade(x: f64, y: f64, sprite: Sprite, entities: &mut Entities, events: &mut Events) {
+    let fade_id = entities.spawn(entity()
+        .with(Position(x, y))
+        .with(sprite)
+        .with(Velocity(0.0, 3.0))
+        .with(Age(0.0))
+        .with(Fade(0.3))
+    );
+    events.schedule("world", Duration::from_millis(300), Destroy(fade_id));
 }
 
 pub fn spawn_text(x: f64, y: f64, text: &str, entities: &mut Entities, events: &mut Events) {
     let text_id = entities.spawn(entity()
         .with(Position(x, y))
-        .with(Text { text: text.to_string(), justification: align::CENTER | align::MIDDLE })
+        .with(Text::new(&format!("<pop>{text}</pop>"), align::CENTER | align::MIDDLE).in_font("Small"))
         .with(Velocity(0.0, 2.0))
+        .with(Age(0.0))
     );
     events.schedule("game", Duration::from_millis(600), Destroy(text_id));
 }
@@ -31,18 +72,20 @@ pub fn spawn_text(x: f64, y: f64, text: &str, entities: &mut Entities, events: &
 pub fn spawn_bulb(x: f64, y: f64, entities: &mut Entities, events: &mut Events) {
     let bulb_id = entities.spawn(entity()
         .with(Position(x, y))
-        .with(Sprite::new(4, 5, 2.0, "Sprites"))
+        .with(ScreenSpace)
+        .with(bulb_sprite(4, 5))
         .with(Period(0.6))
         .with(Phase(0.0))
         .with(AnimationCycle(vec![
-            (0.10, Sprite::new(4, 5, 2.0, "Sprites")),
-            (0.20, Sprite::new(5, 5, 2.0, "Sprites")),
-            (0.30, Sprite::new(6, 5, 2.0, "Sprites")),
-            (0.40, Sprite::new(7, 5, 2.0, "Sprites")),
-            (0.60, Sprite::new(6, 5, 2.0, "Sprites")),
-            (0.80, Sprite::new(5, 5, 2.0, "Sprites")),
-            (1.00, Sprite::new(4, 5, 2.0, "Sprites")),
+            (0.10, bulb_sprite(4, 5)),
+            (0.20, bulb_sprite(5, 5)),
+            (0.30, bulb_sprite(6, 5)),
+            (0.40, bulb_sprite(7, 5)),
+            (0.60, bulb_sprite(6, 5)),
+            (0.80, bulb_sprite(5, 5)),
+            (1.00, bulb_sprite(4, 5)),
         ]))
+        .with(PointLight::new(1.0, (255, 200, 120)))
     );
 
     events.schedule("world", Duration::from_millis(600), Destroy(bulb_id));
@@ -52,18 +95,25 @@ pub fn spawn_bulb(x: f64, y: f64, entities: &mut Entities, events: &mut Events)
 pub fn spawn_flashbulb(x: f64, y: f64, entities: &mut Entities, events: &mut Events) {
     let bulb_id = entities.spawn(entity()
         .with(Position(x, y))
-        .with(Sprite::new(4, 5, 2.0, "Sprites"))
+        .with(ScreenSpace)
+        .with(bulb_sprite(4, 5))
         .with(Period(0.3))
         .with(Phase(0.0))
         //        .with(Flicker(true))
         .with(AnimationCycle(vec![
-            (0.15, Sprite::new(4, 5, 2.0, "Sprites")),
-            (0.3, Sprite::new(5, 5, 2.0, "Sprites")),
-            (0.45, Sprite::new(6, 5, 2.0, "Sprites")),
-            (0.6, Sprite::new(7, 5, 2.0, "Sprites")),
-            (1.00, Sprite::new(-1, -1, 2.0, "Sprites")),
+            (0.15, bulb_sprite(4, 5)),
+            (0.3, bulb_sprite(5, 5)),
+            (0.45, bulb_sprite(6, 5)),
+            (0.6, bulb_sprite(7, 5)),
+            (1.00, bulb_sprite(-1, -1)),
         ]))
+        .with(PointLight::new(1.0, (255, 255, 255)))
     );
 
     events.schedule("world", Duration::from_millis(1150), Destroy(bulb_id));
 }
+
+// part of the interface, over the score but under the flashbulbs
+fn bulb_sprite(x: i32, y: i32) -> Sprite {
+    Sprite::new(x, y, Layer::Ui, "Sprites").with_depth(2.0)
+}
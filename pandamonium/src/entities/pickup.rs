@@ -6,7 +6,7 @@ use entity::Entities;
 use crate::app::events::*;
 
 use super::components::*;
-use super::particle::{spawn_spangle, spawn_text};
+use super::particle::{spawn_fade, spawn_spangle, spawn_text};
 
 #[derive(Clone, Constant)]
 pub struct Pickup;
@@ -39,9 +39,10 @@ pub enum OnPickupDo {
 
 pub fn collect_pickup(PickupCollected(id): &PickupCollected, entities: &mut Entities, events: &mut Events)
 {
-    if let Some((Position(x, y), effect, tune, text, action)) = entities.delete(&id)
+    if let Some((Position(x, y), sprite, effect, tune, text, action)) = entities.delete(&id)
     {
-        if let Some(OnPickupEffect::Sparkles) = effect { spawn_spangle(x, y, entities, events); }
+        if let Some(sprite) = sprite { spawn_fade(x, y, sprite, entities, events); }
+        if let Some(OnPickupEffect::Sparkles) = effect { spawn_spangle(x, y, entities); }
         if let Some(OnPickupTune(tune)) = tune { events.fire(tune); }
         if let Some(OnPickupText(text)) = text { spawn_text(x + 0.5, y + 1.0, text, entities, events); }
         match action {
@@ -2,6 +2,7 @@ use crate::app::events::Destroy;
 use crate::entities::components::{Age, Period, Phase, Position};
 use component_derive::{Constant, Variable};
 use engine::events::{Event, Events};
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use entity::{entity, Entities};
 use std::f64::consts::PI;
@@ -19,17 +20,23 @@ struct Angle(f64);
 #[derive(Clone, Variable)]
 struct Center(f64, f64);
 
-pub fn spawn_radials(x: f64, y: f64, h_grid_pos: Vec<i32>, elements: i32, entities: &mut Entities, events: &mut Events)
+pub fn spawn_radials(x: f64, y: f64, h_grid_pos: Vec<i32>, palette: Option<&str>, elements: i32, entities: &mut Entities, events: &mut Events)
 {
     for i in 0..elements {
-        spawn_radial(x, y, h_grid_pos[i as usize % h_grid_pos.len()], i as f64 * (2.0 * PI) / elements as f64, entities, events);
+        // over everything, the interface included
+        let sprite = Sprite::new(h_grid_pos[i as usize % h_grid_pos.len()], 2, Layer::Ui, "Sprites").with_depth(5.0);
+        let sprite = match palette {
+            Some(palette) => sprite.with_palette(palette),
+            None => sprite,
+        };
+        spawn_radial(x, y, sprite, i as f64 * (2.0 * PI) / elements as f64, entities, events);
     }
 }
 
-fn spawn_radial(x: f64, y: f64, h_grid_pos: i32, theta: f64, entities: &mut Entities, events: &mut Events) {
+fn spawn_radial(x: f64, y: f64, sprite: Sprite, theta: f64, entities: &mut Entities, events: &mut Events) {
     let radial_id = entities.spawn(entity()
         .with(Center(x, y))
-        .with(Sprite::new(h_grid_pos, 2, 5.0, "Sprites"))
+        .with(sprite)
         .with(Period(0.6))
         .with(Phase(0.0))
         .with(AngleOffset(theta))
@@ -48,6 +55,7 @@ pub fn radial_events(event: &Event, entities: &mut Entities, _events: &mut Event
 
 pub fn rotate(entities: &mut Entities) {
     entities.apply(|(Phase(phase), AngleOffset(theta))| Angle((phase * 2.0 * PI) + theta));
+    entities.apply(|(Angle(theta), sprite): (Angle, Sprite)| sprite.rotated(theta.to_degrees()));
     entities.apply(|(Center(x, y), Angle(theta), Radius(r))| Position(x + (r * f64::sin(theta)), y + (r * f64::cos(theta))));
 }
 
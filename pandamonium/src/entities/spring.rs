@@ -3,6 +3,7 @@ use std::time::Duration;
 use crate::app::events::Interaction;
 use component_derive::{Constant, Variable};
 use engine::events::{Event, Events};
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
 use engine::shapes::shape::shape::Shape;
 use entity::{entity, Entities};
@@ -23,7 +24,7 @@ pub fn spawn_spring(x: f64, y: f64, entities: &mut Entities) {
         .with(Position(x, y))
         .with(Interacts::Spring)
         .with(TranslatedMesh(Shape::bbox(0.0, 0.0, 1.0, 1.0).translate(&(x, y))))
-        .with(Sprite::new(0, 8, 0.7, "Sprites"))
+        .with(Sprite::new(0, 8, Layer::Items, "Sprites"))
     );
 }
 
@@ -52,17 +53,17 @@ pub fn spring_events(event: &Event, entities: &mut Entities, _events: &mut Event
 fn animate_spring(last_trigger: Option<SinceLastTrigger>) -> Sprite {
     if let Some(SinceLastTrigger(t)) = last_trigger {
         if t < 0.05 {
-            Sprite::new(1, 8, 0.7, "Sprites")
+            Sprite::new(1, 8, Layer::Items, "Sprites")
         } else if t < 0.15 {
-            Sprite::new(2, 8, 0.7, "Sprites")
+            Sprite::new(2, 8, Layer::Items, "Sprites")
         } else if t < 0.3 {
-            Sprite::new(1, 8, 0.7, "Sprites")
+            Sprite::new(1, 8, Layer::Items, "Sprites")
         } else if t < 0.5 {
-            Sprite::new(2, 8, 0.7, "Sprites")
+            Sprite::new(2, 8, Layer::Items, "Sprites")
         } else {
-            Sprite::new(1, 8, 0.7, "Sprites")
+            Sprite::new(1, 8, Layer::Items, "Sprites")
         }
     } else {
-        Sprite::new(0, 8, 0.7, "Sprites")
+        Sprite::new(0, 8, Layer::Items, "Sprites")
     }
 }
\ No newline at end of file
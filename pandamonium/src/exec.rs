@@ -1,11 +1,19 @@
+use std::collections::HashMap;
+
 use sdl2::image::{self, InitFlag};
 use sdl2::keyboard::Keycode;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 use sdl2::EventPump;
 
+use engine::audio::backend::{AudioBackend, NullAudio};
+use engine::audio::device::initialise_audio;
+use engine::error::EngineError;
 use engine::events::Events;
 use engine::game_loop::run_game_loop;
+use engine::graphics::backend::RenderBackend;
+use engine::graphics::display::DisplayConfig;
+use engine::graphics::font::Font;
 use engine::graphics::renderer::Renderer;
 
 use crate::app::app::App;
@@ -14,21 +22,29 @@ use crate::controller::Controller;
 use crate::screens::screens::Screen;
 use crate::screens::title::Title;
 
-const COLUMNS: usize = 30;
-const ROWS: usize = 20;
+pub const COLUMNS: usize = 30;
+pub const ROWS: usize = 20;
+const TILE_SIZE: u32 = 12;
+const WINDOW_SCALE: u32 = 3;
 
-pub fn run() -> Result<(), String> {
-    let sdl_context = sdl2::init()?;
-    let video_subsystem = sdl_context.video()?;
-    let game_controller_subsystem = sdl_context.game_controller()?;
-    // let audio_device = initialise_audio(&sdl_context)?;
+pub fn run() -> Result<(), EngineError> {
+    let sdl_context = sdl2::init().map_err(EngineError::Render)?;
+    let video_subsystem = sdl_context.video().map_err(EngineError::Render)?;
+    let game_controller_subsystem = sdl_context.game_controller().map_err(EngineError::Input)?;
+    // a missing or busy sound device shouldn't stop the game, it just plays without sound
+    let audio: Box<dyn AudioBackend> = match initialise_audio(&sdl_context) {
+        Ok(audio) => Box::new(audio),
+        Err(err) => {
+            eprintln!("Could not open audio, playing without sound: {err}");
+            Box::new(NullAudio)
+        }
+    };
 
-    image::init(InitFlag::PNG | InitFlag::JPG)?;
+    let _image_context = image::init(InitFlag::PNG | InitFlag::JPG).map_err(EngineError::Asset)?;
 
-    let window = video_subsystem.window("rust-sdl2 demo", 0, 0)
-        .fullscreen_desktop()
-        .build()
-        .expect("could not initialize video subsystem");
+    let display = DisplayConfig::new(COLUMNS as u32 * TILE_SIZE * WINDOW_SCALE, ROWS as u32 * TILE_SIZE * WINDOW_SCALE)
+        .with_args(std::env::args().skip(1));
+    let window = display.open_window(&video_subsystem, "rust-sdl2 demo")?;
 
     video_subsystem.text_input().start();
 
@@ -36,37 +52,40 @@ pub fn run() -> Result<(), String> {
         .into_canvas()
         .present_vsync()
         .build()
-        .expect("could not make a canvas");
+        .map_err(|err| EngineError::Render(err.to_string()))?;
 
     let texture_creator = canvas.texture_creator();
 
-    let assets = Assets::new(&texture_creator)?;
+    let mut sheets = HashMap::new();
+    let assets = Assets::new(&texture_creator, &mut sheets)?;
 
     let mut renderer = Renderer::new(
         canvas,
         &texture_creator,
-        &assets.sheets,
+        sheets,
         COLUMNS as u32,
         ROWS as u32,
-        12,
-        12,
-    ).unwrap();
+        TILE_SIZE,
+        TILE_SIZE,
+    )?.with_integer_scaling(display.integer_scaling)?
+        .with_post_filters(display.post_filters.clone())
+        .with_recording(display.recording);
+    renderer.add_font("Small", Font::standard("SmallText", 4, 6).with_width(":!.,", 3).with_width(" ", 2));
 
     let events = Events::new();
 
     let app = App {
         video_subsystem,
         game_controller_subsystem,
-        // audio_device,
+        audio,
         active_controller: None,
         controller: Controller::new(Keycode::Z, Keycode::X, Keycode::RShift),
         assets: &assets,
-        screen: Screen::TitleScreen(Title),
+        screen: Screen::TitleScreen(Title::default()),
+        transition: None,
         scores: App::starting_scores(),
     };
 
-    let mut event_pump: EventPump = sdl_context.event_pump()?;
-    run_game_loop(app, &mut renderer, &mut event_pump, 1, events)?;
-
-    Ok(())
+    let mut event_pump: EventPump = sdl_context.event_pump().map_err(EngineError::Input)?;
+    run_game_loop(app, &mut renderer, &mut event_pump, 1, events)
 }
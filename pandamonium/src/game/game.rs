@@ -5,13 +5,48 @@ use crate::app::events::*;
 use crate::entities::hero::PandaType;
 use crate::world::world::World;
 use component_derive::Event;
+use sdl2::controller::Button;
+use sdl2::event::Event as SdlEvent;
+use sdl2::keyboard::Keycode;
+use engine::error::EngineError;
 use engine::events::*;
 use engine::game_loop::*;
-use engine::graphics::renderer::{align, Renderer, Text};
+use engine::graphics::backend::RenderBackend;
+use engine::graphics::renderer::{align, Text};
+use engine::graphics::frames::SpriteFrames;
+use engine::graphics::layer::Layer;
 use engine::graphics::sprite::Sprite;
+use engine::ui::Ui;
+
+const MAX_MULTIPLIER: u32 = 5;
+
+// the score bar's pieces, looked up when the assets load, with a badge for every multiplier from 1 up
+pub struct HudSprites {
+    multipliers: Vec<Sprite>,
+    score_left: Sprite,
+    score_middle: Sprite,
+    score_right: Sprite,
+}
+
+impl HudSprites {
+    pub fn new(frames: &SpriteFrames) -> Result<Self, EngineError> {
+        Ok(HudSprites {
+            multipliers: (1..=MAX_MULTIPLIER)
+                .map(|multiplier| frames.sprite(&format!("hud/multiplier/{multiplier}"), Layer::Ui))
+                .collect::<Result<Vec<Sprite>, EngineError>>()?,
+            score_left: frames.sprite("hud/score/left", Layer::Ui)?,
+            score_middle: frames.sprite("hud/score/middle", Layer::Ui)?,
+            score_right: frames.sprite("hud/score/right", Layer::Ui)?,
+        })
+    }
+
+    fn multiplier(&self, multiplier: u32) -> &Sprite {
+        &self.multipliers[multiplier.clamp(1, MAX_MULTIPLIER) as usize - 1]
+    }
+}
 
 pub struct Game<'a> {
-    pub assets: &'a Assets<'a>,
+    pub assets: &'a Assets,
     pub world: World,
     pub score: u32,
     pub multiplier: u32,
@@ -19,6 +54,7 @@ pub struct Game<'a> {
     pub current_level: String,
     pub panda_type: PandaType,
     pub pause: f64,
+    pub menu: Option<Ui>,
 }
 #[derive(Event)]
 struct Pause(f64);
@@ -30,7 +66,7 @@ struct IncreaseMultiplier;
 struct NewLevel(String);
 
 impl<'a> Game<'a> {
-    pub fn new(panda_type: PandaType, assets: &'a Assets<'a>, events: &mut Events) -> Game<'a> {
+    pub fn new(panda_type: PandaType, assets: &'a Assets, events: &mut Events) -> Game<'a> {
         let world: World = World::new(
             &assets,
             &"start".to_string(),
@@ -46,38 +82,59 @@ impl<'a> Game<'a> {
             panda_type,
             current_level: "start".to_string(),
             pause: 0.0,
+            menu: None,
         }
     }
-}
 
-fn multiplier_sprite(multiplier: u32) -> Sprite {
-    match multiplier {
-        1 => Sprite::new(5, 4, 0.0, "Walls"),
-        2 => Sprite::new(5, 5, 0.0, "Walls"),
-        3 => Sprite::new(5, 6, 0.0, "Walls"),
-        4 => Sprite::new(6, 5, 0.0, "Walls"),
-        5 => Sprite::new(6, 6, 0.0, "Walls"),
-        _ => Sprite::new(0, 0, 0.0, "Walls")
+    fn pause_menu(&mut self, events: &mut Events) {
+        let Some(menu) = self.menu.as_mut() else { return };
+        menu.begin();
+        menu.panel(9.0, 6.0, 12, 7);
+        menu.label(Text::new("PAUSED", align::CENTER | align::MIDDLE), 15.0, 11.5);
+        let resume = menu.button("Resume", 11.0, 9.0, 8.0);
+        menu.toggle("Hitboxes", &mut self.world.show_collisions, 11.0, 8.0, 8.0);
+        let quit = menu.button("Quit", 11.0, 7.0, 8.0);
+        let back = menu.back();
+        menu.end();
+
+        if quit {
+            events.fire(ShowTitleScreen());
+        }
+        if resume || back || quit {
+            self.menu = None;
+        }
     }
 }
 
-impl<'a> GameLoop<'a, Renderer<'a>> for Game<'a> {
-    fn render(&self, renderer: &mut Renderer<'a>) -> Result<(), String> {
+impl<'a, R: RenderBackend> GameLoop<'a, R> for Game<'a> {
+    fn render(&self, renderer: &mut R) -> Result<(), EngineError> {
         self.world.render(renderer)?;
-        renderer.draw_sprite(&multiplier_sprite(self.multiplier), 12.0, 19.0);
-        renderer.draw_sprite(&Sprite::new(8, 6, 0.0, "Walls"), 13.0, 19.0);
-        renderer.draw_sprite(&Sprite::new(9, 6, 0.0, "Walls"), 14.0, 19.0);
-        renderer.draw_sprite(&Sprite::new(9, 6, 0.0, "Walls"), 15.0, 19.0);
-        renderer.draw_sprite(&Sprite::new(10, 6, 0.0, "Walls"), 16.0, 19.0);
+        let hud = &self.assets.hud;
+        renderer.draw_sprite(hud.multiplier(self.multiplier), 12.0, 19.0);
+        renderer.draw_sprite(&hud.score_left, 13.0, 19.0);
+        renderer.draw_sprite(&hud.score_middle, 14.0, 19.0);
+        renderer.draw_sprite(&hud.score_middle, 15.0, 19.0);
+        renderer.draw_sprite(&hud.score_right, 16.0, 19.0);
 
         renderer.draw_text(
-            &Text { text: self.score.to_string(), justification: align::RIGHT | align::MIDDLE },
+            &Text::new(&self.score.to_string(), align::RIGHT | align::MIDDLE),
             16.75,
             19.5);
+
+        if let Some(menu) = &self.menu {
+            menu.render(renderer);
+        }
         Ok(())
     }
 
-    fn event(&mut self, event: &Event, mut events: &mut Events) -> Result<(), String> {
+    fn event(&mut self, event: &Event, mut events: &mut Events) -> Result<(), EngineError> {
+        if let Some(SdlEvent::KeyDown { keycode: Some(Keycode::P), repeat: false, .. } | SdlEvent::ControllerButtonDown { button: Button::Start, .. }) = event.unwrap() {
+            self.menu = match self.menu {
+                Some(_) => None,
+                None => Some(Ui::new("Ui")),
+            };
+        }
+
         event.apply(|score| {
             match score {
                 Score::Points(p) => self.score += *p * self.multiplier,
@@ -87,7 +144,7 @@ impl<'a> GameLoop<'a, Renderer<'a>> for Game<'a> {
                     if self.fruit_collected == 5
                     {
                         events.fire(IncreaseMultiplier);
-                        events.fire(SpawnText(15.0, 10.0, "Fruit Salad!".to_string()))
+                        events.fire(SpawnText(15.0, 10.0, "<wave><rainbow>Fruit Salad!</rainbow></wave>".to_string()))
                     }
                 },
                 Score::Double => self.score *= 2
@@ -120,15 +177,17 @@ impl<'a> GameLoop<'a, Renderer<'a>> for Game<'a> {
 
         event.apply(|IncreaseMultiplier| {
             self.multiplier += 1;
-            self.multiplier = self.multiplier.clamp(1, 5);
+            self.multiplier = self.multiplier.clamp(1, MAX_MULTIPLIER);
         });
 
         event.apply(|NewLevel(level)| {
+            let show_collisions = self.world.show_collisions;
             self.world = World::new(
                 &self.assets,
                 level,
                 self.panda_type,
                 &mut events);
+            self.world.show_collisions = show_collisions;
             self.current_level = level.clone();
             self.fruit_collected = 0;
         });
@@ -137,13 +196,24 @@ impl<'a> GameLoop<'a, Renderer<'a>> for Game<'a> {
             self.pause = *pause;
         });
 
+        // nothing moves while the menu is up; it lays itself out on each update instead
+        if let Some(menu) = self.menu.as_mut() {
+            menu.on_event(event);
+            if event.unwrap::<Duration>().is_some() {
+                self.pause_menu(events);
+            }
+            return Ok(());
+        }
+
         if let Some(duration) = event.unwrap::<Duration>() {
             self.pause -= duration.as_secs_f64();
             self.pause = f64::max(self.pause, 0.0);
             if self.pause > 0.0 {
+                // the world stands still, but its camera effects play out over the pause
+                self.world.effects.update(duration);
                 return Ok(());
             }
         }
-        self.world.event(event, events)
+        GameLoop::<R>::event(&mut self.world, event, events)
     }
 }
\ No newline at end of file
@@ -1,9 +1,11 @@
 use sdl2::event::Event as SdlEvent;
 use sdl2::keyboard::Keycode;
 
+use engine::error::EngineError;
 use engine::events::{Event, Events};
 use engine::game_loop::GameLoop;
-use engine::graphics::renderer::{align, Renderer, Text};
+use engine::graphics::backend::RenderBackend;
+use engine::graphics::renderer::{align, Text};
 
 use crate::app::app::HiScore;
 use crate::app::events::{NewGame, ShowTitleScreen, UpdateHiScores};
@@ -49,20 +51,20 @@ fn new_hiscore_index(latest_score: u32, scores: &Vec<HiScore>) -> usize {
     return 99;
 }
 
-impl<'a> GameLoop<'a, Renderer<'a>> for Scores {
-    fn render(&self, renderer: &mut Renderer<'a>) -> Result<(), String> {
-        renderer.draw_text(&Text { text: "HIGH SCORES".to_string(), justification: align::CENTER | align::MIDDLE }, 13.0, 14.0);
+impl<'a, R: RenderBackend> GameLoop<'a, R> for Scores {
+    fn render(&self, renderer: &mut R) -> Result<(), EngineError> {
+        renderer.draw_text(&Text::new("HIGH SCORES", align::CENTER | align::MIDDLE), 13.0, 14.0);
 
         for (index, HiScore { name, score }) in self.scores.iter().enumerate() {
             if index < 10 {
-                renderer.draw_text(&Text { text: name.to_string(), justification: align::RIGHT | align::MIDDLE }, 13.0, 12.0 - index as f64);
-                renderer.draw_text(&Text { text: score.to_string(), justification: align::LEFT | align::MIDDLE }, 14.0, 12.0 - index as f64);
+                renderer.draw_text(&Text::plain(name, align::RIGHT | align::MIDDLE), 13.0, 12.0 - index as f64);
+                renderer.draw_text(&Text::new(&score.to_string(), align::LEFT | align::MIDDLE), 14.0, 12.0 - index as f64);
             }
         }
         Ok(())
     }
 
-    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), String> {
+    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), EngineError> {
         if self.new_hiscore_index > 10 {
             event.apply(|e| {
                 match e {
@@ -1,6 +1,7 @@
+use engine::error::EngineError;
 use engine::events::{Event, Events};
 use engine::game_loop::GameLoop;
-use engine::graphics::renderer::Renderer;
+use engine::graphics::backend::RenderBackend;
 
 use crate::game::game::Game;
 
@@ -13,8 +14,8 @@ pub enum Screen<'a> {
     HiScoreScreen(Scores),
 }
 
-impl<'a> GameLoop<'a, Renderer<'a>> for Screen<'a> {
-    fn render(&self, renderer: &mut Renderer<'a>) -> Result<(), String> {
+impl<'a, R: RenderBackend> GameLoop<'a, R> for Screen<'a> {
+    fn render(&self, renderer: &mut R) -> Result<(), EngineError> {
         match self {
             Screen::GameScreen(game) => game.render(renderer),
             Screen::TitleScreen(title) => title.render(renderer),
@@ -22,11 +23,11 @@ impl<'a> GameLoop<'a, Renderer<'a>> for Screen<'a> {
         }
     }
 
-    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), String> {
+    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), EngineError> {
         match self {
-            Screen::GameScreen(game) => game.event(event, events),
-            Screen::TitleScreen(title) => title.event(event, events),
-            Screen::HiScoreScreen(scores) => scores.event(event, events)
+            Screen::GameScreen(game) => GameLoop::<R>::event(game, event, events),
+            Screen::TitleScreen(title) => GameLoop::<R>::event(title, event, events),
+            Screen::HiScoreScreen(scores) => GameLoop::<R>::event(scores, event, events)
         }
     }
 }
\ No newline at end of file
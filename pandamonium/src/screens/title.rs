@@ -1,29 +1,53 @@
+use std::time::Duration;
+
 use sdl2::event::Event as SdlEvent;
 use sdl2::keyboard::Keycode;
 
+use engine::error::EngineError;
 use engine::events::{Event, Events};
 use engine::game_loop::GameLoop;
 use engine::graphics::renderer::align;
-use engine::graphics::renderer::Renderer;
+use engine::graphics::backend::RenderBackend;
 use engine::graphics::renderer::Text;
+use engine::ui::Ui;
 
 use crate::app::events::{NewGame, ShowHighScores};
 use crate::entities::hero::PandaType;
 
-pub struct Title;
+pub struct Title {
+    ui: Ui,
+}
+
+impl Default for Title {
+    fn default() -> Self {
+        Title { ui: Ui::new("Ui") }
+    }
+}
 
-impl<'a> GameLoop<'a, Renderer<'a>> for Title {
-    fn render(&self, renderer: &mut Renderer<'a>) -> Result<(), String> {
-        renderer.draw_text(&Text { text: "PANDAMONIUM".to_string(), justification: align::CENTER | align::MIDDLE }, 13.0, 10.0);
-        renderer.draw_text(&Text { text: "1: play as Blue".to_string(), justification: align::CENTER | align::MIDDLE }, 13.0, 5.0);
-        renderer.draw_text(&Text { text: "2: play as Redd".to_string(), justification: align::CENTER | align::MIDDLE }, 13.0, 4.0);
-        renderer.draw_text(&Text { text: "H: see hiscores".to_string(), justification: align::CENTER | align::MIDDLE }, 13.0, 3.0);
+impl Title {
+    fn menu(&mut self, events: &mut Events) {
+        self.ui.begin();
+        self.ui.panel(8.0, 2.0, 10, 5);
+        match self.ui.list(&["Play as Blue", "Play as Redd", "Hiscores"], 9.0, 6.0, 8.0) {
+            Some(0) => events.fire(NewGame(PandaType::GiantPanda)),
+            Some(1) => events.fire(NewGame(PandaType::RedPanda)),
+            Some(_) => events.fire(ShowHighScores()),
+            None => {}
+        }
+        self.ui.end();
+    }
+}
 
+impl<'a, R: RenderBackend> GameLoop<'a, R> for Title {
+    fn render(&self, renderer: &mut R) -> Result<(), EngineError> {
+        renderer.draw_text(&Text::new("PANDAMONIUM", align::CENTER | align::MIDDLE), 13.0, 10.0);
+        self.ui.render(renderer);
 
         Ok(())
     }
 
-    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), String> {
+    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), EngineError> {
+        // the number keys still work as shortcuts
         event.apply(|e| {
             match e {
                 SdlEvent::KeyDown { keycode: Some(Keycode::Num1), .. } => events.fire(NewGame(PandaType::GiantPanda)),
@@ -33,6 +57,10 @@ impl<'a> GameLoop<'a, Renderer<'a>> for Title {
                 _otherwise => {}
             }
         });
+        self.ui.on_event(event);
+        if event.unwrap::<Duration>().is_some() {
+            self.menu(events);
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,13 +1,14 @@
 use std::collections::HashSet;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use image::Rgb;
 use image::RgbImage;
 
 use entity::Entities;
 use entity::Id;
+use entity::Not;
 
-use crate::app::assets::Assets;
+use crate::app::assets::{Assets, LevelLayer, TileDef};
 use crate::app::events::*;
 use crate::entities::bell::*;
 use crate::entities::bubble::*;
@@ -25,11 +26,22 @@ use crate::entities::pickup::*;
 use crate::entities::radial::*;
 use crate::entities::spring::spawn_spring;
 use crate::music::countdown::countdown;
+use engine::error::EngineError;
 use engine::events::*;
 use engine::game_loop::*;
-use engine::graphics::renderer::Renderer;
+use engine::graphics::backend::RenderBackend;
+use engine::graphics::animation::Animation;
+use engine::graphics::camera::Camera;
+use engine::graphics::effects::{CameraEffects, CameraFlash, CameraZoom};
+use engine::particles::Emitter;
+use engine::graphics::parallax::ParallaxLayer;
+use engine::graphics::renderer::Text;
+use engine::graphics::layer::Layer;
+use engine::graphics::lighting::{ambient_light, PointLight};
 use engine::graphics::sprite::Sprite;
+use engine::graphics::viewport::Viewport;
 use engine::map::Map;
+use engine::profiler::StageTimed;
 use engine::shapes::shape::collision::Collision;
 use engine::shapes::shape::shape::{Shape, BLOCK};
 use engine::shapes::vec2d::{Vec2d, UNIT_X, UNIT_Y};
@@ -37,6 +49,7 @@ use TileType::{DECORATION, STONE, LEDGE, WATER};
 use crate::controllers::physics::translate;
 use crate::entities::bubble::{spawn_bubble, BubbleHit};
 use crate::entities::crumbler::spawn_crumbler;
+use crate::exec::{COLUMNS, ROWS};
 
 #[derive(Clone, Eq, PartialEq)]
 pub enum TileType {
@@ -49,58 +62,85 @@ pub enum TileType {
 #[derive(Clone)]
 pub struct Tile {
     sprite: Sprite,
+    animation: Option<Animation>,
     shape: Shape,
     tile: TileType,
 }
 
+impl Tile {
+    fn new(tile: &TileDef, sheet: &str, x: u32, y: u32, tile_type: TileType) -> Self {
+        let animation = if tile.animation.is_empty() {
+            None
+        } else {
+            Some(Animation::new(tile.animation.iter()
+                .map(|(frame_x, frame_y, duration)| (Sprite::new(*frame_x as i32, *frame_y as i32, Layer::Tiles, sheet), *duration))
+                .collect()))
+        };
+        Tile {
+            sprite: Sprite::new(tile.x as i32, tile.y as i32, Layer::Tiles, sheet),
+            animation,
+            shape: BLOCK.translate(&(x as f64, y as f64)),
+            tile: tile_type,
+        }
+    }
+
+    fn sprite_at(&self, time: f64) -> &Sprite {
+        self.animation.as_ref().and_then(|animation| animation.frame_at(time)).unwrap_or(&self.sprite)
+    }
+}
+
 pub struct World {
     pub maps: Vec<Map<Tile>>,
+    pub backdrops: Vec<ParallaxLayer>,
     pub entities: Entities,
+    pub camera: Camera,
+    pub effects: CameraEffects,
+    pub width: u32,
+    pub darkness: f64,
+    pub time: f64,
+    pub show_collisions: bool,
 }
 
 impl World {
     pub fn new(assets: &Assets, level: &String, panda_type: PandaType, events: &mut Events) -> Self {
         events.clear_schedule("world");
         let mut maps: Vec<Map<Tile>> = Vec::new();
+        let mut backdrops: Vec<ParallaxLayer> = Vec::new();
         let mut entities = Entities::new();
 
         let level = assets.levels.get(level).unwrap();
+        let poses = assets.poses(panda_type).clone();
         let next_level = level.next_level.clone();
         let next_bonus = level.next_bonus.clone();
+        let (width, height) = (level.width, level.height);
+        let mut hero_start = (0.0, 0.0);
         for layer in &level.layers {
-            let mut map = Map::new(28, 18);
-            for ((x, y), tile_ref) in layer.iter() {
+            if layer.is_backdrop() {
+                backdrops.push(backdrop(assets, layer, width, height));
+                continue;
+            }
+            let mut map = Map::new(width as usize, height as usize);
+            for ((x, y), tile_ref) in layer.tiles.iter() {
                 if let Some(tile) = assets.tiles.get(&tile_ref) {
                     if let Some(tile_type) = &tile.user_type {
                         match tile_type.as_str() {
                             "Wall" => {
-                                map.put(*x as i32, *y as i32, Tile {
-                                    sprite: Sprite::new(tile.x as i32, tile.y as i32, -1.0, &tile_ref.sheet),
-                                    shape: BLOCK.translate(&(*x as f64, *y as f64)),
-                                    tile: STONE,
-                                });
+                                map.put(*x as i32, *y as i32, Tile::new(tile, &tile_ref.sheet, *x, *y, STONE));
                             }
                             "Ledge" => {
-                                map.put(*x as i32, *y as i32, Tile {
-                                    sprite: Sprite::new(tile.x as i32, tile.y as i32, -1.0, &tile_ref.sheet),
-                                    shape: BLOCK.translate(&(*x as f64, *y as f64)),
-                                    tile: LEDGE,
-                                });
+                                map.put(*x as i32, *y as i32, Tile::new(tile, &tile_ref.sheet, *x, *y, LEDGE));
                             }
                             "Water" => {
-                                map.put(*x as i32, *y as i32, Tile {
-                                    sprite: Sprite::new(tile.x as i32, tile.y as i32, -1.0, &tile_ref.sheet),
-                                    shape: BLOCK.translate(&(*x as f64, *y as f64)),
-                                    tile: WATER,
-                                });
+                                map.put(*x as i32, *y as i32, Tile::new(tile, &tile_ref.sheet, *x, *y, WATER));
                             }
                             "Hero" => {
-                                let radial_balls = match panda_type {
-                                    PandaType::GiantPanda => vec![4,5],
-                                    PandaType::RedPanda => vec![6, 5]
+                                hero_start = (*x as f64, *y as f64);
+                                let palette = match panda_type {
+                                    PandaType::GiantPanda => None,
+                                    PandaType::RedPanda => Some("Redd")
                                 };
-                                spawn_radials(*x as f64, *y as f64, radial_balls, 6, &mut entities, events);
-                                events.schedule("world", Duration::from_millis(2400), SpawnHero(*x as f64, *y as f64, panda_type));
+                                spawn_radials(*x as f64, *y as f64, vec![4, 5], palette, 6, &mut entities, events);
+                                events.schedule("world", Duration::from_millis(2400), SpawnHero(*x as f64, *y as f64, poses.clone()));
                             }
                             "Coin" => spawn_coin(*x as f64, *y as f64, &mut entities),
                             "Lockbox" => spawn_lockbox(*x as f64, *y as f64, &mut entities),
@@ -123,11 +163,7 @@ impl World {
                             _otherwise => {}
                         }
                     } else {
-                        map.put(*x as i32, *y as i32, Tile {
-                            sprite: Sprite::new(tile.x as i32, tile.y as i32, -1.0, &tile_ref.sheet),
-                            shape: BLOCK.translate(&(*x as f64, *y as f64)),
-                            tile: DECORATION,
-                        });
+                        map.put(*x as i32, *y as i32, Tile::new(tile, &tile_ref.sheet, *x, *y, DECORATION));
                     }
                 }
             }
@@ -162,13 +198,43 @@ impl World {
 
         countdown(events);
 
+        let mut camera = Camera::new((COLUMNS - 2) as f64, (ROWS - 2) as f64)
+            .with_dead_zone(6.0, 4.0)
+            .with_bounds(0.0, 0.0, width as f64, height as f64);
+        camera.center_on(hero_start.0 + 0.5, hero_start.1 + 0.5);
+
         World {
             maps,
+            backdrops,
             entities,
+            camera,
+            effects: CameraEffects::new(0.3),
+            width,
+            darkness: level.darkness,
+            time: 0.0,
+            show_collisions: false,
         }
     }
 }
 
+fn backdrop(assets: &Assets, layer: &LevelLayer, width: u32, height: u32) -> ParallaxLayer {
+    let (parallax_x, parallax_y) = layer.parallax;
+    let (scroll_x, scroll_y) = layer.scroll;
+    let mut backdrop = ParallaxLayer::new(width as f64, height as f64)
+        .with_factor(parallax_x, parallax_y)
+        .with_scroll(scroll_x, scroll_y)
+        .with_offset(1.0, 1.0);
+    if layer.repeat {
+        backdrop = backdrop.repeating();
+    }
+    for ((x, y), tile_ref) in layer.tiles.iter() {
+        if let Some(tile) = assets.tiles.get(&tile_ref) {
+            backdrop = backdrop.with_tile(*x as f64, *y as f64, Sprite::new(tile.x as i32, tile.y as i32, Layer::Background, &tile_ref.sheet));
+        }
+    }
+    backdrop
+}
+
 fn pixels(image: &RgbImage, color: &Rgb<u8>) -> HashSet<(i32, i32)> {
     let mut pixels = HashSet::new();
 
@@ -184,42 +250,134 @@ fn pixels(image: &RgbImage, color: &Rgb<u8>) -> HashSet<(i32, i32)> {
     pixels
 }
 
-impl<'a> GameLoop<'a, Renderer<'a>> for World {
-    fn render(&self, renderer: &mut Renderer<'a>) -> Result<(), String> {
+impl<'a, R: RenderBackend> GameLoop<'a, R> for World {
+    fn render(&self, renderer: &mut R) -> Result<(), EngineError> {
+        let camera = self.effects.apply(&self.camera);
+        let view = Shape::bbox(camera.x, camera.y, camera.width, camera.height);
+        // the level is kept inside the border, under the HUD; everything in it is drawn a tile up and right
+        // of where it is, so the camera looks a tile up and right to match
+        let play_area = Viewport::new(1.0, 1.0, camera.width, camera.height);
+        let drawing_camera = camera.clone().shifted(1.0, 1.0);
+        renderer.set_viewport(Some(&play_area));
+        renderer.set_camera(Some(&drawing_camera));
+        if self.darkness > 0.0 {
+            renderer.set_ambient_light(Some(ambient_light(self.darkness)));
+            self.entities.for_each(|(Position(x, y), light, _): (Position, PointLight, Not<ScreenSpace>)| {
+                renderer.draw_light(x + 1.5, y + 1.5, &light);
+            });
+        }
+        for backdrop in &self.backdrops {
+            renderer.draw_layer(backdrop, &camera);
+        }
         for map in &self.maps {
-            map.tiles().for_each(|(position, tile)|
-                renderer.draw_sprite(&tile.sprite, (position.x + 1) as f64, (position.y + 1) as f64)
+            map.overlapping(&view, &(0.0, 0.0)).for_each(|(position, tile)|
+                renderer.draw_sprite(tile.sprite_at(self.time), (position.x + 1) as f64, (position.y + 1) as f64)
             );
         }
 
-        self.entities.for_each(|(Position(x, y), sprite)| {
-            renderer.draw_sprite(&sprite, x + 1.0, y + 1.0);
+        self.entities.for_each(|(Position(x, y), sprite, _): (Position, Sprite, Not<ScreenSpace>)| {
+            if in_view(&camera, x, y, &sprite) {
+                renderer.draw_sprite(&sprite, x + 1.0, y + 1.0);
+            }
         });
 
-        self.entities.for_each(|(Position(x, y), text)| {
-            renderer.draw_text(&text, x, y)
+        self.entities.for_each(|emitter: Emitter| {
+            emitter.sprites().into_iter()
+                .filter(|(x, y, sprite)| in_view(&camera, *x, *y, sprite))
+                .for_each(|(x, y, sprite)| renderer.draw_sprite(sprite, x + 1.0, y + 1.0));
         });
 
+        self.entities.for_each(|(Position(x, y), text, age): (Position, Text, Option<Age>)| {
+            let time = age.map_or(0.0, |Age(age)| age);
+            renderer.draw_text(&text.at_time(time), x, y)
+        });
+
+        if self.show_collisions {
+            draw_collisions(self, &view, renderer);
+        }
+        renderer.set_camera(None);
+        renderer.set_viewport(None);
+
+        self.entities.for_each(|(Position(x, y), sprite, ScreenSpace)| {
+            renderer.draw_sprite(&sprite, x + 1.0, y + 1.0);
+        });
+        if self.darkness > 0.0 {
+            self.entities.for_each(|(Position(x, y), light, ScreenSpace)| {
+                renderer.draw_light(x + 1.5, y + 1.5, &light);
+            });
+        }
+
+        if let Some(colour) = self.effects.flash_colour() {
+            renderer.fill_rect(0.0, 0.0, COLUMNS as f64, ROWS as f64, colour);
+        }
+
         Ok(())
     }
 
-    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), String> {
+    fn event(&mut self, event: &Event, events: &mut Events) -> Result<(), EngineError> {
+        event.apply(|ToggleCollisionOverlay| self.show_collisions = !self.show_collisions);
+        event.apply(|Fail| {
+            events.fire(CameraFlash((255, 255, 255), Duration::from_millis(500)));
+            events.fire(CameraZoom(0.15, Duration::from_millis(400)));
+        });
+        self.effects.event(event);
         entity_events(event, &mut self.entities, events);
         event.apply(|dt| update(self, dt, events));
         Ok(())
     }
 }
 
+// a tile's grace all round, for sprites scaled or rotated out of their cells
+fn in_view(camera: &Camera, x: f64, y: f64, sprite: &Sprite) -> bool {
+    camera.can_see(x - 1.0, y - 1.0, sprite.width as f64 + 2.0, sprite.height as f64 + 2.0)
+}
+
+// everything is drawn one tile up and right of where it collides, the same as the sprites
+fn draw_collisions<R: RenderBackend>(world: &World, view: &Shape, renderer: &mut R) {
+    let offset = (1.0, 1.0);
+    for map in &world.maps {
+        map.overlapping(view, &(0.0, 0.0)).for_each(|(_, tile)| match tile.tile {
+            STONE => renderer.draw_shape(&tile.shape.translate(&offset), (255, 0, 0, 160)),
+            LEDGE => renderer.draw_shape(&tile.shape.translate(&offset), (255, 160, 0, 160)),
+            WATER | DECORATION => {}
+        });
+    }
+
+    world.entities.for_each(|TranslatedMesh(shape)| renderer.draw_shape(&shape.translate(&offset), (0, 255, 0, 255)));
+    world.entities.for_each(|TranslatedContextMesh(shape)| renderer.draw_shape(&shape.translate(&offset), (0, 160, 255, 255)));
+
+    // velocities are shown as the distance covered in a tenth of a second, pushes magnified to be visible at all
+    world.entities.for_each(|(Position(x, y), Velocity(dx, dy))| {
+        let center = (x + 1.5, y + 1.5);
+        renderer.draw_line(center, (center.0 + dx * 0.1, center.1 + dy * 0.1), (255, 255, 0, 255));
+    });
+    world.entities.for_each(|(Position(x, y), LastPush(px, py))| {
+        let center = (x + 1.5, y + 1.5);
+        renderer.draw_line(center, (center.0 + px * 10.0, center.1 + py * 10.0), (255, 0, 255, 255));
+    });
+}
 
 fn update<'a>(world: &mut World, dt: &Duration, events: &mut Events) {
+    clamp_to_map(world.width as f64, &mut world.entities);
     events.elapse("world", dt);
+    world.time += dt.as_secs_f64();
+    world.effects.update(dt);
+    world.backdrops.iter_mut().for_each(|backdrop| backdrop.update(dt));
     phase(&mut world.entities, dt);
     animation_cycle(&mut world.entities);
     age(dt, &mut world.entities);
+    fade(&mut world.entities);
     flicker(&mut world.entities);
+    let collision_start = Instant::now();
     map_collisions(&mut world.entities, &world.maps, events);
     item_collisions(&world.entities, events);
+    events.fire(StageTimed("collision", collision_start.elapsed()));
     apply_translations(&mut world.entities);
+    follow_hero(&mut world.camera, &world.entities);
+}
+
+fn follow_hero(camera: &mut Camera, entities: &Entities) {
+    entities.for_each(|(Hero, Position(x, y))| camera.follow(x + 0.5, y + 0.5));
 }
 
 fn map_collisions(entities: &mut Entities, maps: &Vec<Map<Tile>>, events: &mut Events) {
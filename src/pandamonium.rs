@@ -1,3 +1,5 @@
-fn main() -> Result<(), String> {
+use engine::error::EngineError;
+
+fn main() -> Result<(), EngineError> {
     pandamonium::exec::run()
-}
\ No newline at end of file
+}